@@ -2,4 +2,15 @@ fn main() {
     if std::env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("windows") {
         println!("cargo:rustc-link-lib=dylib=dbghelp");
     }
+
+    // The test binary exposes an annotation registry and a JIT descriptor, which
+    // the writer can only find if the symbols are exported
+    if matches!(
+        std::env::var("CARGO_CFG_TARGET_OS").as_deref(),
+        Ok("linux" | "android")
+    ) {
+        for symbol in ["minidump_writer_annotations", "__jit_debug_descriptor"] {
+            println!("cargo:rustc-link-arg-bin=test=-Wl,--export-dynamic-symbol={symbol}");
+        }
+    }
 }
@@ -56,6 +56,24 @@ impl CrashContext {
     }
 }
 
+/// Allows the process `handler`, typically an out-of-process crash handler,
+/// to ptrace the calling process.
+///
+/// When Yama's `ptrace_scope` is 1, the default on many distributions, a
+/// process can only be traced by its ancestors unless it declares a tracer with
+/// `prctl(PR_SET_PTRACER)`. Without Yama, this does nothing.
+pub fn set_ptracer(handler: libc::pid_t) -> std::io::Result<()> {
+    // SAFETY: syscall
+    if unsafe { libc::prctl(libc::PR_SET_PTRACER, handler as libc::c_ulong) } == 0 {
+        return Ok(());
+    }
+    match std::io::Error::last_os_error() {
+        // Yama isn't enabled
+        e if e.raw_os_error() == Some(libc::EINVAL) => Ok(()),
+        e => Err(e),
+    }
+}
+
 #[repr(C)]
 #[derive(Clone)]
 #[doc(hidden)]
@@ -66,6 +84,24 @@ pub struct sigset_t {
     __val: [u64; 16],
 }
 
+impl sigset_t {
+    /// Returns the set as a bitmask, where bit `n - 1` is set if signal `n` is
+    /// a member of the set.
+    ///
+    /// The kernel only defines 64 signals, so the remaining words of the set
+    /// (which exist for userspace ABI reasons) are always empty.
+    pub fn mask(&self) -> u64 {
+        #[cfg(target_pointer_width = "64")]
+        {
+            self.__val[0]
+        }
+        #[cfg(target_pointer_width = "32")]
+        {
+            u64::from(self.__val[0]) | (u64::from(self.__val[1]) << 32)
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Clone)]
 #[doc(hidden)]
@@ -441,4 +477,9 @@ mod test {
             std::mem::size_of::<super::ucontext_t>()
         );
     }
+
+    #[test]
+    fn set_ptracer() {
+        super::set_ptracer(std::process::id() as libc::pid_t).unwrap();
+    }
 }
@@ -1,6 +1,9 @@
 #![no_std]
 #![cfg(any(target_os = "linux", target_os = "android"))]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 pub mod local;
 pub mod regs;
 
@@ -2,12 +2,22 @@ use core::ffi::c_int;
 
 #[derive(Debug, thiserror::Error, serde::Serialize, serde::Deserialize)]
 pub enum Error {
+    #[error("the process no longer exists")]
+    ProcessGone,
+    #[error("failed to open a pidfd for the process: {0}")]
+    PidfdOpenFailed(c_int),
+    #[error("failed to check that the process still exists: {0}")]
+    ProcessCheckFailed(c_int),
     #[error("failed to send SIGSTOP to process: {0}")]
     SigStopFailed(c_int),
     #[error("failed to send SIGCONT to process: {0}")]
     SigContFailed(c_int),
     #[error("failed to attach to process: {0}")]
     PtraceAttachFailed(c_int),
+    #[error("failed to seize process: {0}")]
+    PtraceSeizeFailed(c_int),
+    #[error("failed to interrupt process: {0}")]
+    PtraceInterruptFailed(c_int),
     #[error("failed to detach from process: {0}")]
     PtraceDetachFailed(c_int),
     #[error("failed to peek at process user section: {0}")]
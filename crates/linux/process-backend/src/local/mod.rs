@@ -19,9 +19,78 @@ type PtraceRequestType = core::ffi::c_uint;
 #[cfg(not(target_env = "gnu"))]
 type PtraceRequestType = core::ffi::c_int;
 
+// Not defined by libc for every target
+const PTRACE_SEIZE: PtraceRequestType = 0x4206;
+const PTRACE_INTERRUPT: PtraceRequestType = 0x4207;
+const PTRACE_EVENT_STOP: c_int = 128;
+
+/// Directories opened by the dumper before it was confined, e.g. with Landlock or seccomp, for a
+/// [`Backend`] to access the filesystem through. Each is a file descriptor opened with
+/// `O_DIRECTORY`.
+///
+/// Such a backend accesses files relative to these directories, and never by absolute path, so it
+/// needs the following syscalls:
+///
+/// - `openat`, `newfstatat`/`fstatat64`, `readlinkat`, `getdents64`, `read`, `pread64`, `fstat`,
+///   `mmap`, `munmap` and `close` to read files and directories
+/// - `pidfd_open` and `pidfd_send_signal`, or `kill` before Linux 5.3, to stop and continue the
+///   process
+/// - `ptrace` and `wait4` to suspend threads and read their registers
+/// - `process_vm_readv` to read the process's memory. Should it fail, `mem` is read through
+///   `/proc/<pid>`, which needs `fcntl` to keep a duplicate of it open, then `ptrace` is used.
+#[derive(Debug)]
+pub struct PreopenedDirs {
+    /// `/proc/<pid>` of the process
+    pub proc_pid: OwnedFd,
+    /// `/proc`, for procfs files of the dumper and of the system, such as `cpuinfo`
+    pub proc: OwnedFd,
+    /// `/etc`, for the OS release
+    pub etc: OwnedFd,
+    /// `/`, for module files and anything else
+    pub root: OwnedFd,
+}
+
+impl PreopenedDirs {
+    /// Opens the directories for the process `pid`
+    pub fn open(pid: pid_t) -> Result<Self, Error> {
+        fn open_dir(path: &CStr) -> Result<OwnedFd, Error> {
+            let fd = unsafe {
+                libc::open(
+                    path.as_ptr(),
+                    libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+                )
+            };
+            if fd == -1 {
+                return Err(Error::OpenDirFailed(errno()));
+            }
+            Ok(unsafe { OwnedFd::new(fd) })
+        }
+
+        let mut path = [0u8; 32];
+        Ok(Self {
+            proc_pid: open_dir(proc_pid_path(pid, &mut path))?,
+            proc: open_dir(c"/proc")?,
+            etc: open_dir(c"/etc")?,
+            root: open_dir(c"/")?,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Dirs {
+    proc: OwnedFd,
+    etc: OwnedFd,
+    root: OwnedFd,
+}
+
 #[derive(Debug)]
 pub struct Backend {
     pid: pid_t,
+    /// A pidfd for the process, once opened with [`Backend::open_process`]
+    pidfd: Option<OwnedFd>,
+    /// `/proc/<pid>`, once opened with [`Backend::open_process`] or if preopened
+    proc_dir: Option<OwnedFd>,
+    preopened_dirs: Option<Dirs>,
     syscall_invoker: RefCell<SyscallInvoker>,
 }
 
@@ -29,22 +98,86 @@ impl Backend {
     pub fn new(pid: libc::pid_t) -> Self {
         Self {
             pid,
+            pidfd: None,
+            proc_dir: None,
+            preopened_dirs: None,
             syscall_invoker: Default::default(),
         }
     }
+
+    /// Creates a backend that accesses the filesystem only through the given directories
+    pub fn with_preopened_dirs(pid: libc::pid_t, dirs: PreopenedDirs) -> Self {
+        Self {
+            proc_dir: Some(dirs.proc_pid),
+            preopened_dirs: Some(Dirs {
+                proc: dirs.proc,
+                etc: dirs.etc,
+                root: dirs.root,
+            }),
+            ..Self::new(pid)
+        }
+    }
     pub fn process_reader(&self) -> ProcessReader {
-        ProcessReader(process_reader::ProcessReader::new(self.pid))
+        ProcessReader(match &self.proc_dir {
+            Some(proc_dir) => {
+                process_reader::ProcessReader::new_in_proc_dir(self.pid, proc_dir.as_raw_fd())
+            }
+            None => process_reader::ProcessReader::new(self.pid),
+        })
     }
-    pub fn stop_process(&self) -> Result<(), Error> {
-        self.standard_syscall(|| unsafe { libc::kill(self.pid, libc::SIGSTOP) })
-            .map_err(Error::SigStopFailed)?;
+
+    /// Opens a pidfd and the `/proc/<pid>` directory of the process, so that if the process dies
+    /// and its pid is reused, signals and procfs accesses fail instead of reaching the new
+    /// process.
+    ///
+    /// The directory is opened after the pidfd, and is only known to belong to the process if the
+    /// process is still alive afterwards, which is checked through the pidfd. Without pidfd
+    /// support (Linux < 5.3), the process is still addressed by its pid. A preopened directory is
+    /// kept.
+    pub fn open_process(&mut self) -> Result<(), Error> {
+        let pid = self.pid;
+        let pidfd = match self
+            .standard_syscall(|| unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) })
+        {
+            Ok(fd) => Some(unsafe { OwnedFd::new(fd as c_int) }),
+            Err(libc::ESRCH) => return Err(Error::ProcessGone),
+            Err(libc::ENOSYS) => None,
+            Err(e) => return Err(Error::PidfdOpenFailed(e)),
+        };
+
+        let mut path = [0u8; 32];
+        let proc_dir = match &self.proc_dir {
+            Some(_) => None,
+            None => match self.open_dir_fd(proc_pid_path(pid, &mut path)) {
+                Ok(fd) => Some(fd),
+                Err(Error::OpenDirFailed(libc::ENOENT)) => return Err(Error::ProcessGone),
+                Err(e) => return Err(e),
+            },
+        };
+
+        if let Some(pidfd) = &pidfd {
+            // Signal 0 only checks that the process exists. A permission error also means it does.
+            match self.standard_syscall(|| unsafe { pidfd_send_signal(pidfd.as_raw_fd(), 0) }) {
+                Ok(_) | Err(libc::EPERM) => {}
+                Err(libc::ESRCH) => return Err(Error::ProcessGone),
+                Err(e) => return Err(Error::ProcessCheckFailed(e)),
+            }
+            if proc_dir.is_some() {
+                self.proc_dir = proc_dir;
+            }
+        }
+        self.pidfd = pidfd;
         Ok(())
     }
 
+    pub fn stop_process(&self) -> Result<(), Error> {
+        self.signal_process(libc::SIGSTOP)
+            .map_err(Error::SigStopFailed)
+    }
+
     pub fn continue_process(&self) -> Result<(), Error> {
-        self.standard_syscall(|| unsafe { libc::kill(self.pid, libc::SIGCONT) })
-            .map_err(Error::SigContFailed)?;
-        Ok(())
+        self.signal_process(libc::SIGCONT)
+            .map_err(Error::SigContFailed)
     }
 
     pub fn suspend_thread(&self, tid: libc::pid_t) -> Result<(), Error> {
@@ -53,6 +186,61 @@ impl Backend {
         })
         .map_err(Error::PtraceAttachFailed)?;
 
+        // Any signal will stop the thread, make sure it is SIGSTOP. Otherwise, this signal will be
+        // delivered after PTRACE_DETACH, and the thread will enter the "T (stopped)" state.
+        self.wait_for_stop(tid, |status| libc::WSTOPSIG(status) == libc::SIGSTOP)
+    }
+
+    /// Attaches to the thread with `PTRACE_SEIZE` and asks it to stop, without waiting for it to,
+    /// so that many threads can be stopped at once. [`Self::wait_for_interrupt`] must then be
+    /// called for the thread.
+    ///
+    /// Unlike [`Self::suspend_thread`], this doesn't send the thread a `SIGSTOP`, but needs Linux
+    /// 3.4. Only if seizing the thread fails, with [`Error::PtraceSeizeFailed`], is it left
+    /// untraced, so that it can still be attached to in the old way. If interrupting it fails, it
+    /// is detached from again, once stopped with a `SIGSTOP` if the process isn't.
+    pub fn interrupt_thread(&self, tid: libc::pid_t) -> Result<(), Error> {
+        self.standard_syscall(|| unsafe {
+            ptrace(PTRACE_SEIZE, tid, ptr::null_mut(), ptr::null_mut())
+        })
+        .map_err(Error::PtraceSeizeFailed)?;
+        if let Err(e) = self.standard_syscall(|| unsafe {
+            ptrace(PTRACE_INTERRUPT, tid, ptr::null_mut(), ptr::null_mut())
+        }) {
+            // A seized thread that nobody waits for would hang in the next signal-delivery-stop.
+            // It can only be detached from while stopped, which it isn't unless the process is,
+            // so otherwise stop it first. Failing that, it is left seized.
+            if self.ptrace_detach(tid).is_err() {
+                let _ = self.stop_and_detach(tid);
+            }
+            return Err(Error::PtraceInterruptFailed(e));
+        }
+        Ok(())
+    }
+
+    /// Stops a seized thread with `SIGSTOP` and detaches from it, suppressing the signal
+    fn stop_and_detach(&self, tid: libc::pid_t) -> Result<(), Error> {
+        self.standard_syscall(|| unsafe {
+            libc::syscall(libc::SYS_tgkill, self.pid, tid, libc::SIGSTOP)
+        })
+        .map_err(Error::SigStopFailed)?;
+        self.wait_for_stop(tid, |status| libc::WSTOPSIG(status) == libc::SIGSTOP)?;
+        self.ptrace_detach(tid)
+    }
+
+    /// Waits for a thread passed to [`Self::interrupt_thread`] to stop
+    pub fn wait_for_interrupt(&self, tid: libc::pid_t) -> Result<(), Error> {
+        // Threads already in a group-stop report it in the same way
+        self.wait_for_stop(tid, |status| status >> 16 == PTRACE_EVENT_STOP)
+    }
+
+    /// Waits for a traced thread to enter a stop that `is_expected` accepts, passing on the
+    /// signals it receives meanwhile
+    fn wait_for_stop(
+        &self,
+        tid: libc::pid_t,
+        is_expected: impl Fn(c_int) -> bool,
+    ) -> Result<(), Error> {
         loop {
             let mut status = 0;
             if let Err(e) =
@@ -69,14 +257,10 @@ impl Backend {
                 Err(Error::UnexpectedStatus(status))?;
             }
 
-            let signal = libc::WSTOPSIG(status);
-
-            // Any signal will stop the thread, make sure it is SIGSTOP. Otherwise, this
-            // signal will be delivered after PTRACE_DETACH, and the thread will enter
-            // the "T (stopped)" state.
-            if signal == libc::SIGSTOP {
+            if is_expected(status) {
                 break;
             }
+            let signal = libc::WSTOPSIG(status);
 
             // Signals other than SIGSTOP that are received need to be reinjected,
             // or they will otherwise get lost.
@@ -98,12 +282,14 @@ impl Backend {
         path: &CStr,
         offset: u64,
     ) -> Result<MappedModuleMemoryReader, Error> {
-        MappedModuleMemoryReader::new(&mut self.syscall_invoker.borrow_mut(), path, offset)
+        let (dirfd, path) = self.resolve(path);
+        MappedModuleMemoryReader::new(&mut self.syscall_invoker.borrow_mut(), dirfd, path, offset)
     }
 
     pub fn stat_file(&self, path: &CStr) -> Result<libc::stat, Error> {
+        let (dirfd, path) = self.resolve(path);
         let mut output = unsafe { mem::zeroed::<libc::stat>() };
-        self.standard_syscall(|| unsafe { libc::stat(path.as_ptr(), &mut output) })
+        self.standard_syscall(|| unsafe { libc::fstatat(dirfd, path.as_ptr(), &mut output, 0) })
             .map_err(Error::StatFailed)?;
         Ok(output)
     }
@@ -113,21 +299,27 @@ impl Backend {
     }
 
     pub fn read_dir(&self, path: &CStr) -> Result<DirReader, Error> {
+        let fd = self.open_dir_fd(path)?;
         self.special_syscall(|| unsafe {
-            let dirp = libc::opendir(path.as_ptr());
+            let dirp = libc::fdopendir(fd.as_raw_fd());
             if dirp.is_null() {
                 return Err(());
             }
             Ok(dirp)
         })
-        .map(|dirp| DirReader { dirp, eof: false })
+        .map(|dirp| {
+            // The directory stream owns the fd now
+            mem::forget(fd);
+            DirReader { dirp, eof: false }
+        })
         .map_err(Error::OpenDirFailed)
     }
 
     pub fn read_link(&self, path: &CStr, buf: &mut [u8]) -> Result<usize, Error> {
+        let (dirfd, path) = self.resolve(path);
         let bytes_read = self
             .standard_syscall(|| unsafe {
-                libc::readlink(path.as_ptr(), buf.as_mut_ptr().cast(), buf.len())
+                libc::readlinkat(dirfd, path.as_ptr(), buf.as_mut_ptr().cast(), buf.len())
             })
             .map_err(Error::ReadLinkFailed)?;
 
@@ -180,9 +372,14 @@ impl Backend {
     }
 
     pub fn process_reader_for_file(&self) -> Result<ProcessReader, Error> {
-        process_reader::ProcessReader::for_file(self.pid)
-            .map(ProcessReader)
-            .map_err(Error::ProcessReader)
+        match &self.proc_dir {
+            Some(proc_dir) => {
+                process_reader::ProcessReader::for_file_in_proc_dir(self.pid, proc_dir.as_raw_fd())
+            }
+            None => process_reader::ProcessReader::for_file(self.pid),
+        }
+        .map(ProcessReader)
+        .map_err(Error::ProcessReader)
     }
 
     pub fn process_reader_for_ptrace(&self) -> ProcessReader {
@@ -190,13 +387,62 @@ impl Backend {
     }
 
     fn open_file(&self, path: &CStr) -> Result<OwnedFd, Error> {
+        let (dirfd, path) = self.resolve(path);
         self.standard_syscall(|| unsafe {
-            libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC, 0)
+            libc::openat(dirfd, path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC, 0)
         })
         .map(|fd| unsafe { OwnedFd::new(fd) })
         .map_err(Error::OpenFileFailed)
     }
 
+    fn open_dir_fd(&self, path: &CStr) -> Result<OwnedFd, Error> {
+        let (dirfd, path) = self.resolve(path);
+        self.standard_syscall(|| unsafe {
+            libc::openat(
+                dirfd,
+                path.as_ptr(),
+                libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+                0,
+            )
+        })
+        .map(|fd| unsafe { OwnedFd::new(fd) })
+        .map_err(Error::OpenDirFailed)
+    }
+
+    /// Splits a path into a directory fd and a path to open relative to it, so that paths in
+    /// `/proc/<pid>/` go through the directory opened by [`Backend::open_process`], and all paths
+    /// go through the [`PreopenedDirs`] if there are any.
+    fn resolve<'a>(&self, path: &'a CStr) -> (c_int, &'a CStr) {
+        if let Some(proc_dir) = &self.proc_dir
+            && let Some(relative) = strip_proc_pid_prefix(self.pid, path)
+        {
+            return (proc_dir.as_raw_fd(), relative);
+        }
+        if let Some(dirs) = &self.preopened_dirs {
+            for (prefix, dir) in [
+                (&b"/proc/"[..], &dirs.proc),
+                (b"/etc/", &dirs.etc),
+                (b"/", &dirs.root),
+            ] {
+                if let Some(relative) = strip_dir_prefix(path.to_bytes_with_nul(), prefix) {
+                    return (dir.as_raw_fd(), relative);
+                }
+            }
+        }
+        (libc::AT_FDCWD, path)
+    }
+
+    fn signal_process(&self, signal: c_int) -> Result<(), c_int> {
+        match &self.pidfd {
+            Some(pidfd) => self
+                .standard_syscall(|| unsafe { pidfd_send_signal(pidfd.as_raw_fd(), signal) })
+                .map(drop),
+            None => self
+                .standard_syscall(|| unsafe { libc::kill(self.pid, signal) })
+                .map(drop),
+        }
+    }
+
     fn getregset(&self, _pid: libc::pid_t) -> Result<GenRegs, Error> {
         #[cfg(target_arch = "arm")]
         {
@@ -313,12 +559,22 @@ impl Backend {
             .borrow_mut()
             .fail_one_syscall_with(errno);
     }
+
+    #[cfg(feature = "testing")]
+    pub fn fail_nth_syscall_with(&self, skip: usize, errno: c_int) {
+        self.syscall_invoker
+            .borrow_mut()
+            .fail_nth_syscall_with(skip, errno);
+    }
 }
 
 #[derive(Debug)]
 pub struct FileReader(OwnedFd);
 
 impl FileReader {
+    pub fn as_raw_fd(&self) -> c_int {
+        self.0.as_raw_fd()
+    }
     pub fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
         let rv = unsafe { libc::read(self.0.as_raw_fd(), buf.as_mut_ptr().cast(), buf.len()) };
         if rv == -1 {
@@ -400,11 +656,14 @@ impl ProcessReader {
     }
 }
 
+/// A file descriptor that is closed when dropped, like `std::os::fd::OwnedFd`
 #[derive(Debug)]
-struct OwnedFd(c_int);
+pub struct OwnedFd(c_int);
 
 impl OwnedFd {
-    // SAFETY: Must be a valid fd
+    /// # Safety
+    ///
+    /// `fd` must be an open file descriptor that nothing else owns, as it is closed on drop.
     pub unsafe fn new(fd: c_int) -> Self {
         Self(fd)
     }
@@ -413,6 +672,15 @@ impl OwnedFd {
     }
 }
 
+#[cfg(feature = "std")]
+impl From<std::os::fd::OwnedFd> for OwnedFd {
+    fn from(fd: std::os::fd::OwnedFd) -> Self {
+        use std::os::fd::IntoRawFd;
+        // SAFETY: the fd is owned by `fd`, which gives up ownership of it
+        unsafe { Self::new(fd.into_raw_fd()) }
+    }
+}
+
 impl Drop for OwnedFd {
     fn drop(&mut self) {
         let rv = unsafe { libc::close(self.0) };
@@ -422,6 +690,64 @@ impl Drop for OwnedFd {
     }
 }
 
+/// Sends a signal through a pidfd, like `kill()` does through a pid
+unsafe fn pidfd_send_signal(pidfd: c_int, signal: c_int) -> c_long {
+    unsafe {
+        libc::syscall(
+            libc::SYS_pidfd_send_signal,
+            pidfd,
+            signal,
+            ptr::null_mut::<libc::siginfo_t>(),
+            0,
+        )
+    }
+}
+
+/// Writes the nul-terminated `/proc/<pid>` into `buf`
+fn proc_pid_path(pid: pid_t, buf: &mut [u8; 32]) -> &CStr {
+    const PREFIX: &[u8] = b"/proc/";
+    buf[..PREFIX.len()].copy_from_slice(PREFIX);
+
+    let mut digits = [0u8; 10];
+    let mut n = pid.unsigned_abs();
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+
+    let end = PREFIX.len() + digits.len() - start;
+    buf[PREFIX.len()..end].copy_from_slice(&digits[start..]);
+    buf[end] = 0;
+    CStr::from_bytes_with_nul(&buf[..=end]).unwrap()
+}
+
+/// Returns the rest of `path` if it is in `/proc/<pid>/`
+fn strip_proc_pid_prefix(pid: pid_t, path: &CStr) -> Option<&CStr> {
+    let rest = path.to_bytes_with_nul().strip_prefix(b"/proc/")?;
+    let digits_len = rest.iter().position(|b| !b.is_ascii_digit())?;
+    let (digits, rest) = rest.split_at(digits_len);
+    let path_pid: pid_t = core::str::from_utf8(digits).ok()?.parse().ok()?;
+    if path_pid != pid {
+        return None;
+    }
+    strip_dir_prefix(rest, b"/")
+}
+
+/// Returns the rest of the nul-terminated `path` if it starts with `prefix`, a directory ending in
+/// `/`
+fn strip_dir_prefix<'a>(path: &'a [u8], prefix: &[u8]) -> Option<&'a CStr> {
+    // Relative paths must not be empty
+    match path.strip_prefix(prefix)? {
+        b"\0" => Some(c"."),
+        relative => CStr::from_bytes_with_nul(relative).ok(),
+    }
+}
+
 /// This is just a typesafe wrapper around ptrace(), which is vararg... But this is Rust, and
 /// playing loosey-goosey with types is really more of a C thing ;)
 unsafe fn ptrace(
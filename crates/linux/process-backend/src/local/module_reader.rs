@@ -1,7 +1,7 @@
 use {
     super::{Error, OwnedFd, SyscallInvoker, errno},
     core::{
-        ffi::{CStr, c_void},
+        ffi::{CStr, c_int, c_void},
         mem, ptr,
     },
 };
@@ -16,10 +16,11 @@ pub struct MappedModuleMemoryReader {
 impl MappedModuleMemoryReader {
     pub fn new(
         syscall_invoker: &mut SyscallInvoker,
+        dirfd: c_int,
         path: &CStr,
         start_position: u64,
     ) -> Result<Self, Error> {
-        let fd = Self::open_file(syscall_invoker, path)?;
+        let fd = Self::open_file(syscall_invoker, dirfd, path)?;
 
         // So far, we only ever map files from the start position to EOF - We never specify a
         // max length anywhere.
@@ -66,10 +67,14 @@ impl MappedModuleMemoryReader {
     pub fn is_empty(&self) -> Result<bool, Error> {
         self.len().map(|l| l == 0)
     }
-    fn open_file(syscall_invoker: &mut SyscallInvoker, path: &CStr) -> Result<OwnedFd, Error> {
+    fn open_file(
+        syscall_invoker: &mut SyscallInvoker,
+        dirfd: c_int,
+        path: &CStr,
+    ) -> Result<OwnedFd, Error> {
         syscall_invoker
             .invoke_standard(|| unsafe {
-                libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC, 0)
+                libc::openat(dirfd, path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC, 0)
             })
             .map(|fd| unsafe { OwnedFd::new(fd) })
             .map_err(Error::OpenFileFailed)
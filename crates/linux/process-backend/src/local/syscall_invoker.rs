@@ -1,7 +1,11 @@
 use {super::errno, core::ffi::c_int};
 
 #[derive(Debug, Default)]
-pub struct SyscallInvoker(Option<c_int>);
+pub struct SyscallInvoker {
+    /// The errno the syscall after the next `skip` ones fails with, if testing requested it
+    fail_with: Option<c_int>,
+    skip: usize,
+}
 
 impl SyscallInvoker {
     /// Helper function to invoke a syscall and capture errno if it fails
@@ -16,10 +20,16 @@ impl SyscallInvoker {
     where
         F: FnOnce() -> Result<T, ()>,
     {
-        if let Some(errno) = self.0.take() {
-            Err(errno)
-        } else {
-            f().map_err(|()| errno())
+        match self.fail_with {
+            Some(errno) if self.skip == 0 => {
+                self.fail_with = None;
+                Err(errno)
+            }
+            Some(_) => {
+                self.skip -= 1;
+                f().map_err(|()| errno())
+            }
+            None => f().map_err(|()| errno()),
         }
     }
 
@@ -41,6 +51,13 @@ impl SyscallInvoker {
     /// Force the next syscall to fail with the given errno
     #[cfg(feature = "testing")]
     pub fn fail_one_syscall_with(&mut self, errno: c_int) {
-        self.0 = Some(errno);
+        self.fail_nth_syscall_with(0, errno);
+    }
+
+    /// Force the syscall after the next `skip` ones to fail with the given errno
+    #[cfg(feature = "testing")]
+    pub fn fail_nth_syscall_with(&mut self, skip: usize, errno: c_int) {
+        self.fail_with = Some(errno);
+        self.skip = skip;
     }
 }
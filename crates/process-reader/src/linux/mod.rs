@@ -13,6 +13,9 @@
 //! - [`ProcessReader::for_file`] uses `/proc/<pid>/mem`.
 //! - [`ProcessReader::for_ptrace`] uses `ptrace(PTRACE_PEEKDATA)`.
 //!
+//! [`ProcessReader::new_in_proc_dir`] and [`ProcessReader::for_file_in_proc_dir`]
+//! open `mem` in an already opened `/proc/<pid>` directory instead.
+//!
 //! # Read semantics
 //!
 //! [`ProcessReader::read_at`] attempts to copy bytes from a virtual address in the
@@ -80,7 +83,7 @@
 use self::{error::*, wrapper::*};
 use core::{
     cell::OnceCell,
-    ffi::{CStr, c_long, c_void},
+    ffi::{CStr, c_int, c_long, c_void},
     fmt::Write,
     mem::size_of,
     ptr,
@@ -118,6 +121,8 @@ const PTRACE_PEEKDATA_LEN: usize = size_of::<c_long>();
 pub struct ProcessReader {
     pid: libc::pid_t,
     style: OnceCell<Style>,
+    /// The `/proc/<pid>` directory to open `mem` in, rather than by absolute path
+    proc_dir: Option<Result<File, OpenFailed>>,
 }
 
 impl ProcessReader {
@@ -148,6 +153,26 @@ impl ProcessReader {
         Self {
             pid,
             style: OnceCell::new(),
+            proc_dir: None,
+        }
+    }
+
+    /// Creates a reader like [`ProcessReader::new`] that opens `mem` in the directory `proc_dir`
+    /// rather than `/proc/<pid>/mem` by absolute path, e.g. because the caller can no longer open
+    /// absolute paths, or wants to be sure that the directory belongs to the process.
+    ///
+    /// `proc_dir` must be a file descriptor of the `/proc/<pid>` directory of the process. It is
+    /// only borrowed, the reader keeps a duplicate of it. Should duplicating it fail, the
+    /// `/proc/<pid>/mem` strategy fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pid < 0`. Passing a negative PID is treated as a caller logic
+    /// error.
+    pub fn new_in_proc_dir(pid: libc::pid_t, proc_dir: c_int) -> Self {
+        Self {
+            proc_dir: Some(File::dup(proc_dir)),
+            ..Self::new(pid)
         }
     }
 
@@ -169,6 +194,7 @@ impl ProcessReader {
         Self {
             pid,
             style: OnceCell::from(Style::VirtualMem),
+            proc_dir: None,
         }
     }
 
@@ -200,6 +226,33 @@ impl ProcessReader {
         Ok(Self {
             pid,
             style: OnceCell::from(Style::File(file)),
+            proc_dir: None,
+        })
+    }
+
+    /// Creates a reader like [`ProcessReader::for_file`] that opens `mem` in the directory
+    /// `proc_dir` rather than `/proc/<pid>/mem` by absolute path.
+    ///
+    /// `proc_dir` must be a file descriptor of the `/proc/<pid>` directory of the process. It is
+    /// only used to open `mem`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ReadError`] if `mem` could not be opened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pid < 0`. Passing a negative PID is treated as a caller logic
+    /// error.
+    pub fn for_file_in_proc_dir(pid: libc::pid_t, proc_dir: c_int) -> Result<Self, ReadError> {
+        Self::assert_valid_pid(pid);
+        let file = File::open_at(proc_dir, c"mem")
+            .map_err(FileStrategyError::Open)
+            .map_err(|e| ReadError(ReadErrorInner::FileStrategy(e)))?;
+        Ok(Self {
+            pid,
+            style: OnceCell::from(Style::File(file)),
+            proc_dir: None,
         })
     }
 
@@ -231,6 +284,7 @@ impl ProcessReader {
         Self {
             pid,
             style: OnceCell::from(Style::Ptrace),
+            proc_dir: None,
         }
     }
 
@@ -369,7 +423,7 @@ impl ProcessReader {
             Err(e) => e,
         };
 
-        let file_err = match Self::open_mem_file_for_pid(self.pid) {
+        let file_err = match self.open_mem_file() {
             Ok(file) => match Self::file(&file, address, buf) {
                 Ok(()) => {
                     self.style.set(Style::File(file)).expect(DOUBLE_INIT_MSG);
@@ -397,6 +451,13 @@ impl ProcessReader {
     fn assert_valid_pid(pid: libc::pid_t) {
         assert!(pid >= 0, "pid must be a non-negative process ID");
     }
+    fn open_mem_file(&self) -> Result<File, OpenFailed> {
+        match &self.proc_dir {
+            Some(Ok(proc_dir)) => File::open_at(proc_dir.as_raw_fd(), c"mem"),
+            Some(Err(e)) => Err(OpenFailed(e.0)),
+            None => Self::open_mem_file_for_pid(self.pid),
+        }
+    }
     fn open_mem_file_for_pid(pid: libc::pid_t) -> Result<File, OpenFailed> {
         // The max length of a string that looks like "/proc/{pid}/mem\0"
         //
@@ -38,6 +38,31 @@ impl File {
             }
         }
     }
+    pub(crate) fn as_raw_fd(&self) -> c_int {
+        self.0
+    }
+    /// Opens `path` relative to the directory `dirfd`
+    pub(crate) fn open_at(dirfd: c_int, path: &CStr) -> Result<Self, OpenFailed> {
+        loop {
+            let rv =
+                unsafe { libc::openat(dirfd, path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC, 0) };
+            if rv != -1 {
+                return Ok(Self(rv));
+            }
+            let err = errno();
+            if err != libc::EINTR {
+                return Err(OpenFailed(err));
+            }
+        }
+    }
+    /// Duplicates `fd`, e.g. to keep a directory open
+    pub(crate) fn dup(fd: c_int) -> Result<Self, OpenFailed> {
+        let rv = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+        if rv == -1 {
+            return Err(OpenFailed(errno()));
+        }
+        Ok(Self(rv))
+    }
     pub(crate) fn read_exact_at(
         &self,
         position: usize,
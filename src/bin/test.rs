@@ -11,11 +11,61 @@ mod linux {
         error_graph::ErrorList,
         minidump_writer::{
             LINUX_GATE_LIBRARY_NAME,
+            annotations::{Annotation, AnnotationRegistry},
             minidump_writer::{MinidumpWriter, MinidumpWriterConfig},
         },
         std::ptr,
     };
 
+    static ANNOTATIONS: [Annotation; 2] = [
+        Annotation::new("channel", "nightly"),
+        Annotation::new("request_id", "3f2a"),
+    ];
+
+    #[unsafe(no_mangle)]
+    #[allow(non_upper_case_globals)]
+    pub static minidump_writer_annotations: AnnotationRegistry =
+        AnnotationRegistry::new(&ANNOTATIONS);
+
+    // A JIT that has registered a single code object through the GDB JIT
+    // interface
+    #[repr(C)]
+    pub struct JitCodeEntry {
+        next_entry: *const JitCodeEntry,
+        prev_entry: *const JitCodeEntry,
+        symfile_addr: *const u8,
+        symfile_size: u64,
+    }
+
+    #[repr(C)]
+    pub struct JitDescriptor {
+        version: u32,
+        action_flag: u32,
+        relevant_entry: *const JitCodeEntry,
+        first_entry: *const JitCodeEntry,
+    }
+
+    unsafe impl Sync for JitCodeEntry {}
+    unsafe impl Sync for JitDescriptor {}
+
+    const JIT_OBJECT: &[u8] = include_bytes!("../../tests/tiny.elf");
+
+    static JIT_ENTRY: JitCodeEntry = JitCodeEntry {
+        next_entry: ptr::null(),
+        prev_entry: ptr::null(),
+        symfile_addr: JIT_OBJECT.as_ptr(),
+        symfile_size: JIT_OBJECT.len() as u64,
+    };
+
+    #[unsafe(no_mangle)]
+    #[allow(non_upper_case_globals)]
+    pub static __jit_debug_descriptor: JitDescriptor = JitDescriptor {
+        version: 1,
+        action_flag: 0,
+        relevant_entry: &JIT_ENTRY,
+        first_entry: &JIT_ENTRY,
+    };
+
     macro_rules! test {
         ($x:expr, $errmsg:expr) => {
             if !$x {
@@ -298,6 +348,24 @@ mod linux {
         }
     }
 
+    // Keeps creating short-lived threads, so that some exit while being dumped
+    fn spawn_thread_churn_wait() -> Result<()> {
+        for _ in 0..4 {
+            std::thread::spawn(|| {
+                loop {
+                    let _ = std::thread::spawn(|| {
+                        std::thread::sleep(std::time::Duration::from_micros(200));
+                    })
+                    .join();
+                }
+            });
+        }
+        println!("1");
+        loop {
+            std::thread::park();
+        }
+    }
+
     fn spawn_mmap_wait() -> Result<()> {
         let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
         assert!(page_size > 0);
@@ -381,6 +449,171 @@ mod linux {
         }
     }
 
+    fn spawn_perf_map_wait() -> Result<()> {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let code = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                page_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                -1,
+                0,
+            )
+        };
+        test!(code != libc::MAP_FAILED, "mmap failed");
+        // Stand-in for generated code
+        unsafe {
+            let bytes = std::slice::from_raw_parts_mut(code.cast::<u8>(), page_size);
+            for (idx, byte) in bytes.iter_mut().enumerate() {
+                *byte = (idx % 251) as u8;
+            }
+            test!(
+                libc::mprotect(code, page_size, libc::PROT_READ | libc::PROT_EXEC) == 0,
+                "mprotect failed"
+            );
+        }
+        let code = code as usize;
+
+        // The last entry isn't JIT code, so shouldn't be captured
+        let perf_map = format!(
+            "{:x} 40 jitted_one\n{:x} 20 Lcom/example/Foo;::bar (int)\n{:x} 10 not_jitted\n",
+            code,
+            code + 0x40,
+            spawn_perf_map_wait as *const () as usize,
+        );
+        std::fs::write(format!("/tmp/perf-{}.map", std::process::id()), perf_map)?;
+
+        println!("{code:x}");
+        loop {
+            std::thread::park();
+        }
+    }
+
+    fn spawn_map_files_wait(paths: &[String]) -> Result<()> {
+        use std::os::fd::AsRawFd;
+
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        for path in paths {
+            let file = std::fs::File::open(path)?;
+            let mapped = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    page_size,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            test!(mapped != libc::MAP_FAILED, "mmap failed");
+            print!("{:x} ", mapped as usize);
+        }
+        println!();
+
+        loop {
+            std::thread::park();
+        }
+    }
+
+    /// Forks a child into new PID and UTS namespaces and prints its id, or 0 if
+    /// namespaces can't be created
+    fn spawn_pid_namespace_wait() -> Result<()> {
+        if unsafe { libc::unshare(libc::CLONE_NEWPID | libc::CLONE_NEWUTS) } != 0 {
+            println!("0");
+            return Ok(());
+        }
+        let hostname = b"minidump-container";
+        test!(
+            unsafe { libc::sethostname(hostname.as_ptr().cast(), hostname.len()) } == 0,
+            "sethostname failed"
+        );
+
+        match unsafe { libc::fork() } {
+            -1 => Err("fork failed".into()),
+            0 => loop {
+                std::thread::park();
+            },
+            child => {
+                println!("{child}");
+                unsafe { libc::waitpid(child, ptr::null_mut(), 0) };
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(target_env = "gnu")]
+    fn spawn_assert_wait() -> Result<()> {
+        extern "C" fn on_abort(_signo: libc::c_int) {
+            let ready = b"1\n";
+            unsafe { libc::write(libc::STDOUT_FILENO, ready.as_ptr().cast(), ready.len()) };
+            loop {
+                unsafe { libc::pause() };
+            }
+        }
+
+        unsafe extern "C" {
+            fn __assert_fail(
+                assertion: *const libc::c_char,
+                file: *const libc::c_char,
+                line: libc::c_uint,
+                function: *const libc::c_char,
+            ) -> !;
+        }
+
+        // glibc records the message before raising SIGABRT, which the handler
+        // then blocks in until we're killed
+        unsafe {
+            libc::signal(
+                libc::SIGABRT,
+                on_abort as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+            __assert_fail(
+                c"1 + 1 == 3".as_ptr(),
+                c"test.rs".as_ptr(),
+                42,
+                c"spawn_assert_wait".as_ptr(),
+            )
+        }
+    }
+
+    // Dumps the process `pid` with a watchdog, but dies while the process is
+    // stopped, either by aborting or by hanging until the watchdog kills it
+    fn dump_with_watchdog(pid: i32, behavior: &str) -> Result<()> {
+        struct Destination<'a>(&'a str);
+
+        impl Destination<'_> {
+            fn die(&self) -> ! {
+                match self.0 {
+                    "abort" => std::process::abort(),
+                    _ => loop {
+                        std::thread::park();
+                    },
+                }
+            }
+        }
+
+        impl std::io::Write for Destination<'_> {
+            fn write(&mut self, _buf: &[u8]) -> std::io::Result<usize> {
+                self.die()
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                self.die()
+            }
+        }
+
+        impl std::io::Seek for Destination<'_> {
+            fn seek(&mut self, _pos: std::io::SeekFrom) -> std::io::Result<u64> {
+                self.die()
+            }
+        }
+
+        let mut config = MinidumpWriterConfig::new(pid, pid);
+        config.watchdog(std::time::Duration::from_millis(500));
+        config.write(&mut Destination(behavior))?;
+        Err("the dumper survived writing the dump".into())
+    }
+
     pub(super) fn real_main(args: Vec<String>) -> Result<()> {
         match args.len() {
             1 => match args[0].as_ref() {
@@ -392,6 +625,11 @@ mod linux {
                 "linux_gate_mapping_id" => test_linux_gate_mapping_id(),
                 "spawn_mmap_wait" => spawn_mmap_wait(),
                 "spawn_alloc_wait" => spawn_alloc_wait(),
+                "spawn_thread_churn_wait" => spawn_thread_churn_wait(),
+                "spawn_perf_map_wait" => spawn_perf_map_wait(),
+                "spawn_pid_namespace_wait" => spawn_pid_namespace_wait(),
+                #[cfg(target_env = "gnu")]
+                "spawn_assert_wait" => spawn_assert_wait(),
                 _ => Err("Len 1: Unknown test option".into()),
             },
             2 => match args[0].as_ref() {
@@ -414,6 +652,9 @@ mod linux {
                     let addr1: usize = args[1].parse().unwrap();
                     let addr2: usize = args[2].parse().unwrap();
                     test_find_mappings(addr1, addr2)
+                } else if args[0] == "dump_with_watchdog" {
+                    let pid: i32 = args[1].parse().unwrap();
+                    dump_with_watchdog(pid, &args[2])
                 } else if args[0] == "copy_from_process" {
                     let stack_var: usize = args[1].parse().unwrap();
                     let heap_var: usize = args[2].parse().unwrap();
@@ -428,6 +669,8 @@ mod linux {
                     let mapped_mem: usize = args[2].parse().unwrap();
                     let mem_size: usize = args[3].parse().unwrap();
                     test_merged_mappings(path.to_string(), mapped_mem, mem_size)
+                } else if args[0] == "spawn_map_files_wait" {
+                    spawn_map_files_wait(&args[1..])
                 } else {
                     Err(format!("Len 4: Unknown test option: {}", args[0]).into())
                 }
@@ -8,6 +8,7 @@ cfg_if::cfg_if! {
                 FillMissingAuxvInfo,
                 ThreadName,
                 SuspendThreads,
+                InterruptThread,
                 CpuInfoFileOpen,
             }
         }
@@ -0,0 +1,97 @@
+//! A registry of key/value annotations that a process exposes to the minidump
+//! writer.
+//!
+//! A module of the process exports a dynamic symbol named
+//! [`ANNOTATIONS_SYMBOL`] that refers to an [`AnnotationRegistry`]. When
+//! writing a minidump, every loaded module is searched for the symbol, and the
+//! entries of each registry found are read out of the process and written as
+//! the simple annotations of the Crashpad info stream.
+//!
+//! Shared libraries export `#[unsafe(no_mangle)]` statics by default, but
+//! executables only export symbols the linker is asked to, e.g. with
+//! `-Wl,--export-dynamic-symbol=minidump_writer_annotations`.
+//!
+//! ```
+//! use minidump_writer::annotations::{Annotation, AnnotationRegistry};
+//!
+//! static ENTRIES: [Annotation; 2] = [
+//!     Annotation::new("channel", "nightly"),
+//!     Annotation::new("feature.foo", "enabled"),
+//! ];
+//!
+//! #[unsafe(no_mangle)]
+//! #[allow(non_upper_case_globals)]
+//! pub static minidump_writer_annotations: AnnotationRegistry = AnnotationRegistry::new(&ENTRIES);
+//! ```
+//!
+//! The registry is read from another process, so it can be changed at any time
+//! with plain (volatile) writes, as long as `count` only covers entries whose
+//! strings are valid.
+
+/// The name of the dynamic symbol that refers to an [`AnnotationRegistry`]
+pub const ANNOTATIONS_SYMBOL: &str = "minidump_writer_annotations";
+
+/// The value of [`AnnotationRegistry::magic`], "MWAN" in little-endian
+pub const ANNOTATIONS_MAGIC: u32 = u32::from_le_bytes(*b"MWAN");
+
+/// The current version of the registry layout
+pub const ANNOTATIONS_VERSION: u32 = 1;
+
+/// The most entries read from a single registry
+pub const MAX_ANNOTATIONS: usize = 1024;
+
+/// The longest key or value read from a registry, longer strings are truncated
+pub const MAX_ANNOTATION_LEN: usize = 16 * 1024;
+
+/// The header that [`ANNOTATIONS_SYMBOL`] refers to.
+///
+/// Later versions may only add fields at the end.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct AnnotationRegistry {
+    /// Must be [`ANNOTATIONS_MAGIC`]
+    pub magic: u32,
+    /// Must be [`ANNOTATIONS_VERSION`]
+    pub version: u32,
+    /// The number of entries `entries` points to
+    pub count: usize,
+    pub entries: *const Annotation,
+}
+
+// SAFETY: the registry only refers to immutable data
+unsafe impl Sync for AnnotationRegistry {}
+
+impl AnnotationRegistry {
+    pub const fn new(entries: &'static [Annotation]) -> Self {
+        Self {
+            magic: ANNOTATIONS_MAGIC,
+            version: ANNOTATIONS_VERSION,
+            count: entries.len(),
+            entries: entries.as_ptr(),
+        }
+    }
+}
+
+/// A single key/value pair, both UTF-8 and not nul-terminated.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct Annotation {
+    pub key: *const u8,
+    pub key_len: usize,
+    pub value: *const u8,
+    pub value_len: usize,
+}
+
+// SAFETY: the annotation only refers to immutable data
+unsafe impl Sync for Annotation {}
+
+impl Annotation {
+    pub const fn new(key: &'static str, value: &'static str) -> Self {
+        Self {
+            key: key.as_ptr(),
+            key_len: key.len(),
+            value: value.as_ptr(),
+            value_len: value.len(),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use {
     super::{
         auxv::AuxvType,
-        module_reader::ModuleReaderError,
+        module_reader::{ModuleFiles, ModuleReaderError},
         process_inspection::{self, ProcessInspector},
         serializers::*,
     },
@@ -12,10 +12,12 @@ use {
         process::{MMPermissions, MMapPath, MemoryMaps},
     },
     std::{
+        borrow::Cow,
         ffi::{OsStr, OsString},
+        io::Read,
         mem::size_of,
         os::unix::ffi::{OsStrExt, OsStringExt},
-        path::{Path, PathBuf},
+        path::PathBuf,
     },
 };
 
@@ -61,6 +63,48 @@ pub struct MappingEntry {
 // A list of <MappingInfo, GUID>
 pub type MappingList = Vec<MappingEntry>;
 
+/// The contents of `/proc/<pid>/maps`, or of `/proc/<pid>/smaps` which adds
+/// the memory usage of each mapping, read once so that everything derived from
+/// them describes the same address space
+#[derive(Debug, Default, Clone)]
+pub struct MapsSnapshot {
+    pub contents: Vec<u8>,
+    pub smaps: bool,
+}
+
+impl MapsSnapshot {
+    pub fn read(process_inspector: &ProcessInspector, pid: i32, smaps: bool) -> Result<Self> {
+        let path = format!("/proc/{pid}/{}", if smaps { "smaps" } else { "maps" });
+        let mut contents = Vec::new();
+        process_inspector
+            .read_file(&path)
+            .map_err(MapsReaderError::ReadFileFailed)?
+            .read_to_end(&mut contents)?;
+        Ok(Self { contents, smaps })
+    }
+
+    pub fn memory_maps(&self) -> procfs_core::ProcResult<MemoryMaps> {
+        MemoryMaps::from_read(self.contents.as_slice())
+    }
+
+    /// The contents in the format of `/proc/<pid>/maps`
+    pub fn maps(&self) -> Cow<'_, [u8]> {
+        if !self.smaps {
+            return Cow::Borrowed(&self.contents);
+        }
+        // Unlike the mapping lines, the fields smaps adds after each of them
+        // start with a capital letter
+        Cow::Owned(
+            self.contents
+                .split_inclusive(|&b| b == b'\n')
+                .filter(|line| !line.first().is_some_and(u8::is_ascii_uppercase))
+                .flatten()
+                .copied()
+                .collect(),
+        )
+    }
+}
+
 #[derive(thiserror::Error, Debug, serde::Serialize)]
 pub enum MapsReaderError {
     #[error("failed to read /proc/<pid>/maps")]
@@ -133,6 +177,24 @@ fn sanitize_path(pathname: OsString) -> OsString {
     }
 }
 
+/// Converts the path of a single `/proc/<pid>/maps` entry into the name used
+/// for a [`MappingInfo`].
+pub fn mapping_name(pathname: MMapPath) -> Option<OsString> {
+    match pathname {
+        MMapPath::Path(p) => Some(sanitize_path(p.into())),
+        MMapPath::Heap => Some("[heap]".into()),
+        MMapPath::Stack => Some("[stack]".into()),
+        MMapPath::TStack(i) => Some(format!("[stack:{i}]").into()),
+        MMapPath::Vdso => Some("[vdso]".into()),
+        MMapPath::Vvar => Some("[vvar]".into()),
+        MMapPath::Vsyscall => Some("[vsyscall]".into()),
+        MMapPath::Rollup => Some("[rollup]".into()),
+        MMapPath::Vsys(i) => Some(format!("/SYSV{i:x}").into()),
+        MMapPath::Other(n) => Some(format!("[{n}]").into()),
+        MMapPath::Anonymous => None,
+    }
+}
+
 impl MappingInfo {
     /// Get the mappings for the given process.
     pub fn for_pid(
@@ -140,11 +202,7 @@ impl MappingInfo {
         pid: i32,
         linux_gate_loc: Option<AuxvType>,
     ) -> Result<Vec<Self>> {
-        let maps_path = format!("/proc/{}/maps", pid);
-        let maps_file = process_inspector
-            .read_file(&maps_path)
-            .map_err(MapsReaderError::ReadFileFailed)?;
-        let maps = MemoryMaps::from_read(maps_file)?;
+        let maps = MapsSnapshot::read(process_inspector, pid, false)?.memory_maps()?;
         Self::aggregate(maps, linux_gate_loc)
     }
 
@@ -172,19 +230,7 @@ impl MappingInfo {
             let end_address: usize = mm.address.1.try_into()?;
             let mut offset: usize = mm.offset.try_into()?;
 
-            let mut pathname: Option<OsString> = match mm.pathname {
-                MMapPath::Path(p) => Some(sanitize_path(p.into())),
-                MMapPath::Heap => Some("[heap]".into()),
-                MMapPath::Stack => Some("[stack]".into()),
-                MMapPath::TStack(i) => Some(format!("[stack:{i}]").into()),
-                MMapPath::Vdso => Some("[vdso]".into()),
-                MMapPath::Vvar => Some("[vvar]".into()),
-                MMapPath::Vsyscall => Some("[vsyscall]".into()),
-                MMapPath::Rollup => Some("[rollup]".into()),
-                MMapPath::Vsys(i) => Some(format!("/SYSV{i:x}").into()),
-                MMapPath::Other(n) => Some(format!("[{n}]").into()),
-                MMapPath::Anonymous => None,
-            };
+            let mut pathname = mapping_name(mm.pathname);
 
             let is_path = is_mapping_a_path(pathname.as_deref());
 
@@ -298,9 +344,12 @@ impl MappingInfo {
 
     /// Find the shared object name (SONAME) by examining the ELF information
     /// for the mapping.
-    fn so_name(&self, process_inspector: &ProcessInspector) -> Result<String> {
-        let path = Path::new(self.name.as_deref().unwrap_or_default());
-        super::module_reader::read_soname_from_file(process_inspector, path, self.offset)
+    fn so_name(
+        &self,
+        process_inspector: &ProcessInspector,
+        module_files: &ModuleFiles,
+    ) -> Result<String> {
+        super::module_reader::read_soname_from_file(process_inspector, module_files, self)
             .map_err(MapsReaderError::ReadSoNameFromFileFailed)
     }
 
@@ -312,6 +361,7 @@ impl MappingInfo {
     pub fn get_mapping_effective_path_name_and_version(
         &self,
         process_inspector: &ProcessInspector,
+        module_files: &ModuleFiles,
         soname: Option<String>,
     ) -> Result<(PathBuf, String, Option<SoVersion>)> {
         let mut file_path = PathBuf::from(self.name.clone().unwrap_or_default());
@@ -322,7 +372,8 @@ impl MappingInfo {
         // filesystem name of the module.
 
         // Just use the filesystem name if no SONAME is present.
-        let Some(file_name) = soname.or_else(|| self.so_name(process_inspector).ok()) else {
+        let Some(file_name) = soname.or_else(|| self.so_name(process_inspector, module_files).ok())
+        else {
             //   file_path := /path/to/libname.so
             //   file_name := libname.so
             let file_name = file_path
@@ -731,7 +782,11 @@ a4840000-a4873000 rw-p 09021000 08:12 393449     /data/app/org.mozilla.firefox-1
         let process_inspector = ProcessInspector::local(0);
 
         let (file_path, file_name, _version) = mappings[0]
-            .get_mapping_effective_path_name_and_version(&process_inspector, None)
+            .get_mapping_effective_path_name_and_version(
+                &process_inspector,
+                &ModuleFiles::default(),
+                None,
+            )
             .expect("Couldn't get effective name for mapping");
         assert_eq!(file_name, "libmozgtk.so");
         assert_eq!(
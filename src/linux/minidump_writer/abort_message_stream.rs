@@ -0,0 +1,68 @@
+use super::*;
+
+/// The symbol glibc stores a pointer to the message of `__libc_message` in,
+/// which is what failed assertions and other fatal errors inside glibc use
+/// before calling `abort()`. It points to a `struct abort_msg_s`, whose
+/// nul-terminated message follows an `unsigned int` size.
+///
+/// musl and bionic don't keep the message around in the same way.
+const ABORT_MSG_SYMBOL: &str = "__abort_msg";
+const ABORT_MSG_TEXT_OFFSET: usize = std::mem::size_of::<u32>();
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionAbortMessageError {
+    #[error("Failed to read the dynamic symbols of libc")]
+    ModuleReaderError(#[from] module_reader::ModuleReaderError),
+    #[error("Failed to copy the abort message from the process")]
+    CopyFromProcessError(#[from] CopyFromProcessError),
+}
+
+impl MinidumpWriter {
+    /// Reads the message glibc recorded before aborting the process, if any.
+    pub fn read_abort_message(&self) -> Result<Option<String>, SectionAbortMessageError> {
+        let Some(libc) = self.mappings.iter().find(|m| {
+            m.is_interesting()
+                && m.name
+                    .as_deref()
+                    .and_then(|n| std::path::Path::new(n).file_name())
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("libc.so") || n.starts_with("libc-"))
+        }) else {
+            return Ok(None);
+        };
+
+        let reader = self.process_inspector.process_reader();
+        let Some(symbol) = module_reader::find_dynamic_symbol_in_module(
+            module_reader::ProcessModuleMemoryReader::new(&reader, libc.start_address),
+            ABORT_MSG_SYMBOL,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        let address = (libc.start_address as u64).wrapping_add(symbol.address) as usize;
+        // SAFETY: any bit pattern is a valid usize
+        let abort_msg: usize = unsafe { reader.copy_object(address) }?;
+        if abort_msg == 0 {
+            return Ok(None);
+        }
+
+        let message = reader.copy_nul_terminated_string(abort_msg + ABORT_MSG_TEXT_OFFSET)?;
+        Ok(Some(message.to_string_lossy().into_owned()))
+    }
+
+    pub fn write_abort_message_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionAbortMessageError> {
+        let Some(message) = self.read_abort_message()? else {
+            return Ok(Default::default());
+        };
+
+        let section = MemoryArrayWriter::write_bytes(buffer, message.as_bytes());
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxAbortMessage as u32,
+            location: section.location(),
+        })
+    }
+}
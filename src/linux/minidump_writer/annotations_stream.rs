@@ -0,0 +1,180 @@
+use {
+    super::*,
+    crate::{
+        linux::annotations::{
+            ANNOTATIONS_MAGIC, ANNOTATIONS_SYMBOL, ANNOTATIONS_VERSION, Annotation,
+            AnnotationRegistry, MAX_ANNOTATION_LEN, MAX_ANNOTATIONS,
+        },
+        mem_writer::write_utf8_string_to_location,
+        process_reader::ProcessReader,
+    },
+};
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionAnnotationsError {
+    #[error("Failed to read the annotation registry of {module}")]
+    CopyFromProcessError {
+        module: String,
+        #[source]
+        error: CopyFromProcessError,
+    },
+    #[error(
+        "The annotation registry of {module} has an unknown magic ({magic:#x}) or version ({version})"
+    )]
+    UnknownRegistry {
+        module: String,
+        magic: u32,
+        version: u32,
+    },
+    #[error("The annotation registry of {module} has {count} entries, the rest were skipped")]
+    TooManyAnnotations { module: String, count: usize },
+    #[error("Failed to write to memory")]
+    MemoryWriterError(#[from] MemoryWriterError),
+}
+
+impl MinidumpWriter {
+    /// Reads the entries of the [`AnnotationRegistry`] of every module that
+    /// exports one, in the order the modules are mapped.
+    ///
+    /// If a key appears more than once, the last value is kept.
+    pub fn read_annotations(
+        &self,
+        mut soft_errors: impl WriteErrorList<SectionAnnotationsError>,
+    ) -> Vec<(String, String)> {
+        let reader = self.process_inspector.process_reader();
+        let mut annotations: Vec<(String, String)> = Vec::new();
+
+        for (idx, address) in self.find_exported_symbol(ANNOTATIONS_SYMBOL) {
+            let module = self.mappings[idx]
+                .name
+                .as_deref()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            match read_registry(&reader, address, &module, &mut soft_errors) {
+                Ok(entries) => {
+                    for (key, value) in entries {
+                        match annotations.iter_mut().find(|(k, _)| *k == key) {
+                            Some(existing) => existing.1 = value,
+                            None => annotations.push((key, value)),
+                        }
+                    }
+                }
+                Err(e) => soft_errors.push(e),
+            }
+        }
+
+        annotations
+    }
+
+    /// Writes the annotations exported by the process as the simple annotations
+    /// of a Crashpad info stream.
+    pub fn write_annotations_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        soft_errors: impl WriteErrorList<SectionAnnotationsError>,
+    ) -> Result<MDRawDirectory, SectionAnnotationsError> {
+        let annotations = self.read_annotations(soft_errors);
+        if annotations.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let mut entries = Vec::with_capacity(annotations.len());
+        for (key, value) in &annotations {
+            entries.push(MDRawSimpleStringDictionaryEntry {
+                key: write_utf8_string_to_location(buffer, key)?.rva,
+                value: write_utf8_string_to_location(buffer, value)?.rva,
+            });
+        }
+
+        let dictionary = MemoryWriter::alloc_with_val(
+            buffer,
+            MDRawSimpleStringDictionary {
+                count: entries.len() as u32,
+            },
+        )?;
+        let entries = MemoryArrayWriter::alloc_from_iter(buffer, entries)?;
+        let mut simple_annotations = dictionary.location();
+        simple_annotations.data_size += entries.location().data_size;
+
+        let no_id = GUID {
+            data1: 0,
+            data2: 0,
+            data3: 0,
+            data4: [0; 8],
+        };
+        let info = MemoryWriter::alloc_with_val(
+            buffer,
+            MDRawCrashpadInfo {
+                version: MDRawCrashpadInfo::VERSION,
+                report_id: no_id,
+                client_id: no_id,
+                simple_annotations,
+                module_list: Default::default(),
+            },
+        )?;
+
+        Ok(MDRawDirectory {
+            stream_type: MDStreamType::CrashpadInfoStream as u32,
+            location: info.location(),
+        })
+    }
+}
+
+fn read_registry(
+    reader: &ProcessReader,
+    address: usize,
+    module: &str,
+    soft_errors: &mut impl WriteErrorList<SectionAnnotationsError>,
+) -> Result<Vec<(String, String)>, SectionAnnotationsError> {
+    let copy_error = |error| SectionAnnotationsError::CopyFromProcessError {
+        module: module.to_owned(),
+        error,
+    };
+
+    // SAFETY: the registry only holds integers and raw pointers, which are
+    // valid for any bit pattern.
+    let registry: AnnotationRegistry =
+        unsafe { reader.copy_object(address) }.map_err(copy_error)?;
+    if registry.magic != ANNOTATIONS_MAGIC || registry.version != ANNOTATIONS_VERSION {
+        return Err(SectionAnnotationsError::UnknownRegistry {
+            module: module.to_owned(),
+            magic: registry.magic,
+            version: registry.version,
+        });
+    }
+
+    let count = if registry.count > MAX_ANNOTATIONS {
+        soft_errors.push(SectionAnnotationsError::TooManyAnnotations {
+            module: module.to_owned(),
+            count: registry.count,
+        });
+        MAX_ANNOTATIONS
+    } else {
+        registry.count
+    };
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    // SAFETY: as above
+    let entries: Vec<Annotation> =
+        unsafe { reader.copy_array(registry.entries as usize, count) }.map_err(copy_error)?;
+
+    let read_string = |ptr: *const u8, len: usize| {
+        reader
+            .read_all_to_vec(ptr as usize, len.min(MAX_ANNOTATION_LEN))
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    };
+
+    let mut annotations = Vec::with_capacity(count);
+    for entry in entries {
+        let key = read_string(entry.key, entry.key_len);
+        let value = read_string(entry.value, entry.value_len);
+        match (key, value) {
+            (Ok(key), Ok(value)) => annotations.push((key, value)),
+            (Err(error), _) | (_, Err(error)) => soft_errors.push(copy_error(error)),
+        }
+    }
+    Ok(annotations)
+}
@@ -4,6 +4,9 @@ impl MinidumpWriter {
     /// Write application-provided memory regions.
     pub fn write_app_memory(&mut self, buffer: &mut DumpBuf) -> Result<(), CopyFromProcessError> {
         for app_memory in &self.app_memory {
+            if self.budget.exceeded() {
+                break;
+            }
             let data_copy = Self::copy_from_process(
                 &self.process_inspector,
                 app_memory.ptr,
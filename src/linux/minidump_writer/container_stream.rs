@@ -0,0 +1,179 @@
+use super::*;
+
+/// The namespaces whose inode numbers are recorded, as named in
+/// `/proc/<pid>/ns`
+const NAMESPACES: &[&str] = &["cgroup", "ipc", "mnt", "net", "pid", "time", "user", "uts"];
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionContainerError {
+    #[error("Failed to read the cgroups of the process")]
+    ReadCgroupFailed(
+        #[source]
+        #[serde(serialize_with = "serialize_io_error")]
+        std::io::Error,
+    ),
+    #[error("Failed to stat the {namespace} namespace of the process")]
+    StatNamespaceFailed {
+        namespace: &'static str,
+        #[source]
+        error: process_inspection::Error,
+    },
+    #[error("Failed to get the hostname of the UTS namespace of the process")]
+    HostnameFailed(
+        #[source]
+        #[serde(serialize_with = "serialize_io_error")]
+        std::io::Error,
+    ),
+    #[error("Failed to convert the container identity to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ContainerThread {
+    tid: Pid,
+    namespace_tids: Vec<Pid>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Container {
+    pid: Pid,
+    /// The id of the process in each PID namespace, from the dumper's outward
+    /// to the innermost
+    namespace_pids: Vec<Pid>,
+    threads: Vec<ContainerThread>,
+    /// The lines of `/proc/<pid>/cgroup`
+    cgroups: Vec<String>,
+    /// The inode number of each namespace of the process, which identifies it
+    namespaces: std::collections::BTreeMap<&'static str, u64>,
+    hostname: Option<String>,
+}
+
+// The field is narrower on some 32-bit architectures
+#[allow(clippy::useless_conversion)]
+fn inode(stat: &libc::stat) -> u64 {
+    u64::from(stat.st_ino)
+}
+
+/// The node name in the current thread's UTS namespace
+fn current_hostname() -> Result<String, std::io::Error> {
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // SAFETY: `uname` nul-terminates the fields
+    let nodename = unsafe { std::ffi::CStr::from_ptr(uts.nodename.as_ptr()) };
+    Ok(nodename.to_string_lossy().into_owned())
+}
+
+/// The node name in the UTS namespace `uts`.
+///
+/// A thread can join another UTS namespace without affecting the rest of the
+/// process, so this is done on a thread of its own. It needs `CAP_SYS_ADMIN`
+/// in the user namespace that owns the UTS namespace.
+fn hostname_in_namespace(uts: &process_inspection::FileReader) -> Result<String, std::io::Error> {
+    use std::os::fd::AsRawFd;
+
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if unsafe { libc::setns(uts.as_raw_fd(), libc::CLONE_NEWUTS) } != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                current_hostname()
+            })
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("hostname thread panicked")))
+    })
+}
+
+impl MinidumpWriter {
+    /// Writes what identifies the container the process runs in: its ids and
+    /// those of its threads in each PID namespace, its cgroups, the inode
+    /// numbers of its namespaces and the hostname of its UTS namespace.
+    pub fn write_container_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionContainerError>,
+    ) -> Result<MDRawDirectory, SectionContainerError> {
+        let pid = self.process_id;
+
+        let cgroups = match self
+            .process_inspector
+            .read_file(format!("/proc/{pid}/cgroup"))
+            .map_err(std::io::Error::other)
+            .and_then(|mut file| {
+                let mut s = String::new();
+                file.read_to_string(&mut s)?;
+                Ok(s)
+            }) {
+            Ok(cgroups) => cgroups.lines().map(str::to_owned).collect(),
+            Err(e) => {
+                soft_errors.push(SectionContainerError::ReadCgroupFailed(e));
+                Vec::new()
+            }
+        };
+
+        let mut namespaces = std::collections::BTreeMap::new();
+        for &namespace in NAMESPACES {
+            match self
+                .process_inspector
+                .stat_file(format!("/proc/{pid}/ns/{namespace}"))
+            {
+                Ok(stat) => {
+                    namespaces.insert(namespace, inode(&stat));
+                }
+                // Older kernels lack some of the namespaces
+                Err(e) if e.is_not_found() => {}
+                Err(error) => soft_errors
+                    .push(SectionContainerError::StatNamespaceFailed { namespace, error }),
+            }
+        }
+
+        let own_uts = self
+            .process_inspector
+            .stat_file("/proc/self/ns/uts")
+            .ok()
+            .map(|stat| inode(&stat));
+        let hostname = if namespaces
+            .get("uts")
+            .is_some_and(|uts| Some(*uts) == own_uts)
+        {
+            current_hostname()
+        } else {
+            self.process_inspector
+                .read_file(format!("/proc/{pid}/ns/uts"))
+                .map_err(std::io::Error::other)
+                .and_then(|uts| hostname_in_namespace(&uts))
+        };
+        let hostname = hostname
+            .map_err(|e| soft_errors.push(SectionContainerError::HostnameFailed(e)))
+            .ok();
+
+        let container = Container {
+            pid,
+            namespace_pids: self.namespace_pids.clone(),
+            threads: self
+                .threads
+                .iter()
+                .map(|t| ContainerThread {
+                    tid: t.tid,
+                    namespace_tids: t.namespace_tids.clone(),
+                })
+                .collect(),
+            cgroups,
+            namespaces,
+            hostname,
+        };
+
+        let location =
+            write_json(buffer, &container).map_err(SectionContainerError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxContainer as u32,
+            location,
+        })
+    }
+}
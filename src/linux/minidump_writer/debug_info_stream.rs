@@ -0,0 +1,137 @@
+use {super::*, module_reader::DebugLink};
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionDebugInfoError {
+    #[error("Failed to read the debug information references of {module}")]
+    ModuleReaderError {
+        module: String,
+        #[source]
+        error: module_reader::ModuleReaderError,
+    },
+    #[error("Failed to convert the debug information references to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+/// Where the `.gnu_debugdata` section of a module was copied to in the
+/// minidump.
+#[derive(Debug, serde::Serialize)]
+struct DebugData {
+    rva: u32,
+    data_size: u32,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ModuleDebugInfo {
+    module: String,
+    base_address: u64,
+    debug_link: Option<DebugLink>,
+    debug_data: Option<DebugData>,
+}
+
+/// Whether the error only means the module doesn't have the section.
+fn is_missing_section(error: &module_reader::ModuleReaderError) -> bool {
+    matches!(
+        error,
+        module_reader::ModuleReaderError::NoSections
+            | module_reader::ModuleReaderError::NoDebugLinkSection
+            | module_reader::ModuleReaderError::NoDebugDataSection
+    )
+}
+
+impl MinidumpWriter {
+    /// Writes the `.gnu_debuglink` of every module that has one, so the debug
+    /// package a module needs can be named, and if enabled with
+    /// [`MinidumpWriterConfig::capture_gnu_debugdata`], its `.gnu_debugdata`
+    /// so it can be symbolicated without the package.
+    ///
+    /// Neither section is loaded, so they are read from the module files.
+    pub fn write_debug_info_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionDebugInfoError>,
+    ) -> Result<MDRawDirectory, SectionDebugInfoError> {
+        let mut modules = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+
+        for mapping in &self.mappings {
+            if self.budget.exceeded() {
+                break;
+            }
+            if !mapping.is_interesting() {
+                continue;
+            }
+            let Some(path) = mapping.name.as_deref().map(std::path::Path::new) else {
+                continue;
+            };
+            if !path.is_absolute() || path.starts_with("/dev/") || !seen.insert(path) {
+                continue;
+            }
+
+            let module = path.to_string_lossy().into_owned();
+            let mut push_error = |error| {
+                if !is_missing_section(&error) {
+                    soft_errors.push(SectionDebugInfoError::ModuleReaderError {
+                        module: module.clone(),
+                        error,
+                    });
+                }
+            };
+
+            let reader = match self
+                .module_files
+                .map_module_into_memory(&self.process_inspector, mapping, 0)
+                .and_then(|(module_memory, _)| module_reader::ModuleReader::new(module_memory))
+            {
+                Ok(reader) => reader,
+                Err(e) => {
+                    push_error(e);
+                    continue;
+                }
+            };
+
+            let debug_link = reader
+                .debug_link_from_section()
+                .map_err(&mut push_error)
+                .ok();
+            let debug_data = if self.capture_gnu_debugdata {
+                reader
+                    .debug_data_from_section()
+                    .map(|data| {
+                        let section = MemoryArrayWriter::write_bytes(buffer, &data);
+                        DebugData {
+                            rva: section.location().rva,
+                            data_size: section.location().data_size,
+                        }
+                    })
+                    .map_err(&mut push_error)
+                    .ok()
+            } else {
+                None
+            };
+
+            if debug_link.is_some() || debug_data.is_some() {
+                modules.push(ModuleDebugInfo {
+                    module,
+                    base_address: mapping.start_address as u64,
+                    debug_link,
+                    debug_data,
+                });
+            }
+        }
+
+        if modules.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let location =
+            write_json(buffer, &modules).map_err(SectionDebugInfoError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxDebugInfo as u32,
+            location,
+        })
+    }
+}
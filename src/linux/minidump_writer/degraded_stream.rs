@@ -0,0 +1,90 @@
+use {
+    super::*,
+    crate::linux::thread_info::{ThreadPointers, read_thread_pointers},
+};
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionDegradedError {
+    #[error("Failed to read the stack and instruction pointers of thread {tid}")]
+    ReadThreadPointersFailed {
+        tid: Pid,
+        #[source]
+        error: ThreadInfoError,
+    },
+    #[error("Failed to convert the degraded dump description to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+/// Why the dump lacks information
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum DegradedMode {
+    /// See [`MinidumpWriterConfig::no_ptrace`]
+    NoPtrace,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DegradedThread {
+    tid: Pid,
+    #[serde(flatten)]
+    pointers: ThreadPointers,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Degraded {
+    mode: DegradedMode,
+    /// The threads whose stack and instruction pointers are known, and where
+    /// they were read from
+    threads: Vec<DegradedThread>,
+}
+
+impl MinidumpWriter {
+    /// Marks a dump written without ptrace as degraded, as its thread contexts
+    /// only hold the stack and instruction pointers.
+    ///
+    /// This does nothing unless enabled with [`MinidumpWriterConfig::no_ptrace`].
+    pub fn write_degraded_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionDegradedError>,
+    ) -> Result<MDRawDirectory, SectionDegradedError> {
+        if !self.no_ptrace {
+            return Ok(Default::default());
+        }
+
+        let threads = self
+            .threads
+            .iter()
+            .filter_map(|thread| {
+                match read_thread_pointers(&self.process_inspector, self.process_id, thread.tid) {
+                    Ok(pointers) => Some(DegradedThread {
+                        tid: thread.tid,
+                        pointers,
+                    }),
+                    Err(error) => {
+                        soft_errors.push(SectionDegradedError::ReadThreadPointersFailed {
+                            tid: thread.tid,
+                            error,
+                        });
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        let degraded = Degraded {
+            mode: DegradedMode::NoPtrace,
+            threads,
+        };
+        let location =
+            write_json(buffer, &degraded).map_err(SectionDegradedError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxDegradedDump as u32,
+            location,
+        })
+    }
+}
@@ -5,11 +5,19 @@ use {
         dso_debug::SectionDsoDebugError,
         maps_reader::MapsReaderError,
         minidump_writer::{
+            abort_message_stream::SectionAbortMessageError,
+            annotations_stream::SectionAnnotationsError, container_stream::SectionContainerError,
+            debug_info_stream::SectionDebugInfoError, degraded_stream::SectionDegradedError,
             exception_stream::SectionExceptionStreamError,
-            handle_data_stream::SectionHandleDataStreamError, mappings::SectionMappingsError,
-            memory_info_list_stream::SectionMemInfoListError,
-            memory_list_stream::SectionMemListError, systeminfo_stream::SectionSystemInfoError,
-            thread_list_stream::SectionThreadListError,
+            exited_threads_stream::SectionExitedThreadsError,
+            fault_diagnosis_stream::SectionFaultDiagnosisError,
+            handle_data_stream::SectionHandleDataStreamError, jit_code::SectionJitCodeError,
+            mappings::SectionMappingsError, memory_info_list_stream::SectionMemInfoListError,
+            memory_list_stream::SectionMemListError, memory_usage_stream::SectionMemoryUsageError,
+            module_files_stream::SectionModuleFilesError, perf_map_stream::SectionPerfMapError,
+            siginfo_stream::SectionSigInfoError, signal_context_stream::SectionSignalContextError,
+            symbol_hints_stream::SectionSymbolHintsError,
+            systeminfo_stream::SectionSystemInfoError, thread_list_stream::SectionThreadListError,
             thread_names_stream::SectionThreadNamesError,
         },
         module_reader::ModuleReaderError,
@@ -89,6 +97,58 @@ pub enum WriterError {
     WriteLimitsFailed(#[source] MemoryWriterError),
     #[error("Failed writing handle data stream")]
     WriteHandleDataStreamFailed(#[source] SectionHandleDataStreamError),
+    #[error("{0} exception parameters were requested, only the first 15 were written")]
+    TooManyExceptionParameters(usize),
+    #[error("Failed to diagnose the fault")]
+    DiagnoseFaultFailed(#[source] SectionFaultDiagnosisError),
+    #[error("Failed writing fault diagnosis stream")]
+    WriteFaultDiagnosisStreamFailed(#[source] SectionFaultDiagnosisError),
+    #[error("Failed writing siginfo stream")]
+    WriteSigInfoStreamFailed(#[source] SectionSigInfoError),
+    #[error("Errors occurred while capturing JIT code")]
+    WriteJitCodeErrors(#[source] ErrorList<SectionJitCodeError>),
+    #[error("Failed writing abort message stream")]
+    WriteAbortMessageStreamFailed(#[source] SectionAbortMessageError),
+    #[error("Errors occurred while writing perf map stream")]
+    WritePerfMapErrors(#[source] ErrorList<SectionPerfMapError>),
+    #[error("Failed writing perf map stream")]
+    WritePerfMapStreamFailed(#[source] SectionPerfMapError),
+    #[error("Errors occurred while reading annotations")]
+    WriteAnnotationsErrors(#[source] ErrorList<SectionAnnotationsError>),
+    #[error("Failed writing annotations stream")]
+    WriteAnnotationsStreamFailed(#[source] SectionAnnotationsError),
+    #[error("Errors occurred while reading debug information references")]
+    WriteDebugInfoErrors(#[source] ErrorList<SectionDebugInfoError>),
+    #[error("Failed writing debug information stream")]
+    WriteDebugInfoStreamFailed(#[source] SectionDebugInfoError),
+    #[error("Errors occurred while identifying the container")]
+    WriteContainerErrors(#[source] ErrorList<SectionContainerError>),
+    #[error("Failed writing container stream")]
+    WriteContainerStreamFailed(#[source] SectionContainerError),
+    #[error("Errors occurred while describing the degraded dump")]
+    WriteDegradedErrors(#[source] ErrorList<SectionDegradedError>),
+    #[error("Failed writing degraded dump stream")]
+    WriteDegradedStreamFailed(#[source] SectionDegradedError),
+    #[error("Failed writing exited threads stream")]
+    WriteExitedThreadsStreamFailed(#[source] SectionExitedThreadsError),
+    #[error("Failed writing memory usage stream")]
+    WriteMemoryUsageStreamFailed(#[source] SectionMemoryUsageError),
+    #[error("Errors occurred while reading module file metadata")]
+    WriteModuleFilesErrors(#[source] ErrorList<SectionModuleFilesError>),
+    #[error("Failed writing module file metadata stream")]
+    WriteModuleFilesStreamFailed(#[source] SectionModuleFilesError),
+    #[error("Errors occurred while resolving symbol hints")]
+    WriteSymbolHintsErrors(#[source] ErrorList<SectionSymbolHintsError>),
+    #[error("Failed writing symbol hints stream")]
+    WriteSymbolHintsStreamFailed(#[source] SectionSymbolHintsError),
+    #[error("Errors occurred while writing signal context stream")]
+    WriteSignalContextErrors(#[source] ErrorList<SectionSignalContextError>),
+    #[error("Failed writing signal context stream")]
+    WriteSignalContextStreamFailed(#[source] SectionSignalContextError),
+    #[error("The deadline passed before stream {0:#x} could be written")]
+    StreamSkipped(u32),
+    #[error("Stream {0:#x} ran out of time and is incomplete")]
+    StreamBudgetExceeded(u32),
     #[error("Failed writing handle data stream direction entry")]
     WriteHandleDataStreamDirentFailed(#[source] FileWriterError),
     #[error("Failed to convert soft error list to JSON")]
@@ -127,7 +187,7 @@ pub enum WriterError {
         #[serde(skip)]
         std::num::TryFromIntError,
     ),
-    #[error("failed to suspend thread")]
+    #[error("failed to suspend thread (see `probe::probe` to diagnose permission errors)")]
     SuspendThreadFailed(#[source] process_inspection::Error),
     #[error("failed to resume thread")]
     ResumeThreadFailed(#[source] process_inspection::Error),
@@ -144,6 +204,18 @@ pub enum InitError {
     AndroidLateInitError(#[from] AndroidError),
     #[error("Ptrace does not function within the same process")]
     CannotPtraceSameProcess,
+    #[error("The process no longer exists")]
+    ProcessGone,
+    #[error("Failed to open the process")]
+    OpenProcessFailed(#[source] process_inspection::Error),
+    #[error("The watchdog can't be used when the threads are already traced")]
+    WatchdogWithThreadsAlreadyTraced,
+    #[error("Failed to start the watchdog")]
+    StartWatchdogFailed(
+        #[source]
+        #[serde(serialize_with = "serialize_io_error")]
+        std::io::Error,
+    ),
     #[error("Failed to stop the target process")]
     StopProcessFailed(#[source] StopProcessError),
     #[error("Errors occurred while filling missing Auxv info")]
@@ -160,6 +232,12 @@ pub enum InitError {
         #[serde(serialize_with = "serialize_io_error")]
         std::io::Error,
     ),
+    #[error("Failed to read the PID namespace ids of the process or a thread")]
+    ReadNamespaceIdsFailed(
+        #[source]
+        #[serde(serialize_with = "serialize_io_error")]
+        std::io::Error,
+    ),
     #[error("Proc task directory `{0:?}` is not a directory")]
     ProcPidTaskNotDirectory(String),
     #[error("Errors while enumerating threads")]
@@ -174,6 +252,8 @@ pub enum InitError {
     SuspendThreadsErrors(#[source] ErrorList<WriterError>),
     #[error("No threads left to suspend out of {0}")]
     SuspendNoThreadsLeft(usize),
+    #[error("The process was still creating threads after they were listed {0} more times")]
+    ThreadSetUnstable(usize),
     #[error("Crash thread does not reference principal mapping")]
     PrincipalMappingNotReferenced,
 }
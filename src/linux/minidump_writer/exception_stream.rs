@@ -1,5 +1,29 @@
 use {super::*, minidump_common::errors::ExceptionCodeLinux};
 
+/// The maximum number of [`RequestedException::parameters`] that fit in the
+/// exception record.
+pub const MAX_EXCEPTION_PARAMETERS: usize = 15;
+
+/// Describes why a dump of a process that didn't crash was requested, eg. a
+/// hang or a failed assertion.
+///
+/// This is only used when no crash context is set.
+#[derive(Debug, Clone, Default)]
+pub struct RequestedException {
+    /// Written as the exception code, instead of `DUMP_REQUESTED`
+    pub code: u32,
+    pub flags: u32,
+    /// Written as the exception address, instead of the instruction pointer of
+    /// the blamed thread
+    pub address: u64,
+    /// Written as the exception information. Only the first
+    /// [`MAX_EXCEPTION_PARAMETERS`] fit, any more are reported as a soft error
+    /// and dropped.
+    pub parameters: Vec<u64>,
+    /// A human readable description, written to a comment stream
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Error, serde::Serialize)]
 pub enum SectionExceptionStreamError {
     #[error("Failed to write to memory")]
@@ -7,17 +31,78 @@ pub enum SectionExceptionStreamError {
 }
 
 impl MinidumpWriter {
+    /// Writes the exception stream.
+    ///
+    /// When a crash context is available, `exception_information` describes
+    /// the fault as diagnosed by [`Self::diagnose_fault`], which must already
+    /// be stored in [`Self::fault_diagnosis`]:
+    ///
+    /// | Index | Value |
+    /// |-------|-------|
+    /// | 0 | The [`FaultClass`](super::fault_diagnosis_stream::FaultClass) |
+    /// | 1 | The start address of the mapping the fault was attributed to, or 0 |
+    /// | 2 | For a stack overflow, how many bytes below the stack the fault address is, or 0 |
+    ///
+    /// followed by the parts of the siginfo that don't fit in the rest of the
+    /// exception record. Signed fields are sign-extended.
+    ///
+    /// | Index | Value |
+    /// |-------|-------|
+    /// | 3 | `ssi_errno` |
+    /// | 4 | `ssi_pid`, the sender of a signal sent by a process |
+    /// | 5 | `ssi_uid`, the real user ID of the sender |
+    /// | 6 | `ssi_addr_lsb`, the address granularity of a `SIGBUS` memory error |
+    /// | 7 | `ssi_syscall`, the system call that triggered a seccomp `SIGSYS` |
+    /// | 8 | `ssi_arch`, the `AUDIT_ARCH_*` of that system call |
+    /// | 9 | `ssi_call_addr`, the address of that system call instruction |
+    ///
+    /// The complete siginfo is also written to its own stream.
+    ///
+    /// Without a crash context, the exception is either the
+    /// [`RequestedException`] set on the config, or `DUMP_REQUESTED` at the
+    /// instruction pointer of the blamed thread.
     pub fn write_exception_stream(
         &mut self,
         buffer: &mut DumpBuf,
     ) -> Result<MDRawDirectory, SectionExceptionStreamError> {
         let exception = if let Some(context) = &self.crash_context {
-            MDException {
+            let mut exception = MDException {
                 exception_code: context.inner.siginfo.ssi_signo,
                 exception_flags: context.inner.siginfo.ssi_code as u32,
                 exception_address: context.inner.siginfo.ssi_addr,
                 ..Default::default()
+            };
+            let info = &mut exception.exception_information;
+            if let Some(diagnosis) = &self.fault_diagnosis {
+                info[0] = diagnosis.class as u64;
+                info[1] = diagnosis
+                    .mapping
+                    .as_ref()
+                    .map_or(0, |mapping| mapping.start_address);
+                info[2] = diagnosis.distance_below_stack.unwrap_or(0);
             }
+            let siginfo = &context.inner.siginfo;
+            info[3] = siginfo.ssi_errno as u64;
+            info[4] = siginfo.ssi_pid.into();
+            info[5] = siginfo.ssi_uid.into();
+            info[6] = siginfo.ssi_addr_lsb.into();
+            info[7] = siginfo.ssi_syscall as u64;
+            info[8] = siginfo.ssi_arch.into();
+            info[9] = siginfo.ssi_call_addr;
+            exception.number_parameters = 10;
+            exception
+        } else if let Some(requested) = &self.requested_exception {
+            let mut exception = MDException {
+                exception_code: requested.code,
+                exception_flags: requested.flags,
+                exception_address: requested.address,
+                ..Default::default()
+            };
+            let count = requested.parameters.len().min(MAX_EXCEPTION_PARAMETERS);
+            exception.exception_information[..count]
+                .copy_from_slice(&requested.parameters[..count]);
+            exception.number_parameters = count as u32;
+            exception
         } else {
             let addr = match &self.crashing_thread_context {
                 CrashingThreadContext::CrashContextPlusAddress((_, addr)) => *addr,
@@ -53,4 +138,29 @@ impl MinidumpWriter {
 
         Ok(dirent)
     }
+
+    /// Writes the reason of the [`RequestedException`] as a comment, if there
+    /// is one.
+    pub fn write_comment_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionExceptionStreamError> {
+        let Some(reason) = self
+            .requested_exception
+            .as_ref()
+            .and_then(|requested| requested.reason.as_deref())
+        else {
+            return Ok(Default::default());
+        };
+
+        // Comments are nul terminated
+        let mut comment = Vec::with_capacity(reason.len() + 1);
+        comment.extend_from_slice(reason.as_bytes());
+        comment.push(0);
+        let section = MemoryArrayWriter::write_bytes(buffer, &comment);
+        Ok(MDRawDirectory {
+            stream_type: MDStreamType::CommentStreamA as u32,
+            location: section.location(),
+        })
+    }
 }
@@ -0,0 +1,49 @@
+use super::*;
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionExitedThreadsError {
+    #[error("Failed to convert the exited threads to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ExitedThreads<'a> {
+    /// How many times the threads were listed again after suspending them
+    rescans: usize,
+    /// Whether the suspended threads matched the listed ones in the end, if
+    /// not the process kept creating threads
+    stable: bool,
+    threads: &'a [ExitedThread],
+}
+
+impl MinidumpWriter {
+    /// Lists the threads that exited between being listed and being
+    /// suspended, or that were killed while suspended, which the thread list
+    /// doesn't contain.
+    ///
+    /// This is only written if the writer suspended the threads.
+    pub fn write_exited_threads_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionExitedThreadsError> {
+        if !self.threads_suspended {
+            return Ok(Default::default());
+        }
+
+        let exited = ExitedThreads {
+            rescans: self.thread_rescans,
+            stable: self.thread_set_stable,
+            threads: &self.exited_threads,
+        };
+        let location =
+            write_json(buffer, &exited).map_err(SectionExitedThreadsError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxExitedThreads as u32,
+            location,
+        })
+    }
+}
@@ -0,0 +1,174 @@
+use {super::*, crate::linux::maps_reader::mapping_name, procfs_core::process::MemoryMap};
+
+/// Faults below this address are treated as dereferences of a null pointer
+/// (plus some offset). This is the default `vm.mmap_min_addr`, below which
+/// nothing can be mapped.
+const NULL_PAGE_LIMIT: u64 = 0x10000;
+
+/// The broad kind of fault the crashing thread hit, as stored in
+/// `exception_information[0]` of the exception stream.
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum FaultClass {
+    /// Not a memory fault, or none of the more specific classes apply
+    Other = 0,
+    /// The fault address lies in the guard gap below the crashing thread's
+    /// stack
+    StackOverflow = 1,
+    /// The fault address lies within the first pages of the address space
+    NullDereference = 2,
+    /// The faulting instruction fetch was from a mapping that is not
+    /// executable
+    NonExecutableFetch = 3,
+}
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionFaultDiagnosisError {
+    #[error("the maps of the process couldn't be read")]
+    MapsUnavailable,
+    #[error("Failed to read from procfs")]
+    ProcfsError(
+        #[from]
+        #[serde(serialize_with = "serialize_proc_error")]
+        procfs_core::ProcError,
+    ),
+    #[error("Failed to convert fault diagnosis to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FaultDiagnosis {
+    pub class: FaultClass,
+    pub fault_address: u64,
+    pub instruction_pointer: u64,
+    pub stack_pointer: u64,
+    /// The mapping the fault was attributed to: the stack for a stack
+    /// overflow, or the mapping containing the fault address for a
+    /// non-executable fetch
+    pub mapping: Option<FaultMapping>,
+    /// For a stack overflow, how many bytes below the stack mapping the fault
+    /// address is
+    pub distance_below_stack: Option<u64>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FaultMapping {
+    pub start_address: u64,
+    pub end_address: u64,
+    pub permissions: String,
+    pub name: Option<String>,
+}
+
+impl From<&MemoryMap> for FaultMapping {
+    fn from(mm: &MemoryMap) -> Self {
+        Self {
+            start_address: mm.address.0,
+            end_address: mm.address.1,
+            permissions: mm.perms.as_str(),
+            name: mapping_name(mm.pathname.clone()).map(|n| n.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+impl MinidumpWriter {
+    /// Classifies the fault described by the crash context, if there is one.
+    ///
+    /// This uses the individual entries of [`Self::maps`] rather than
+    /// [`Self::mappings`], as the latter merges the segments of each module and
+    /// so loses their individual permissions.
+    pub fn diagnose_fault(&self) -> Result<Option<FaultDiagnosis>, SectionFaultDiagnosisError> {
+        let Some(crash_context) = &self.crash_context else {
+            return Ok(None);
+        };
+        let siginfo = &crash_context.inner.siginfo;
+        let fault_address = siginfo.ssi_addr;
+        let instruction_pointer = crash_context.get_instruction_pointer() as u64;
+        let stack_pointer = crash_context.get_stack_pointer() as u64;
+
+        let mut diagnosis = FaultDiagnosis {
+            class: FaultClass::Other,
+            fault_address,
+            instruction_pointer,
+            stack_pointer,
+            mapping: None,
+            distance_below_stack: None,
+        };
+
+        let signo = siginfo.ssi_signo as i32;
+        if signo != libc::SIGSEGV && signo != libc::SIGBUS {
+            return Ok(Some(diagnosis));
+        }
+
+        if fault_address < NULL_PAGE_LIMIT {
+            diagnosis.class = FaultClass::NullDereference;
+            return Ok(Some(diagnosis));
+        }
+
+        let maps = self
+            .maps
+            .as_ref()
+            .ok_or(SectionFaultDiagnosisError::MapsUnavailable)?
+            .memory_maps()?;
+
+        let containing = maps
+            .iter()
+            .find(|mm| mm.address.0 <= fault_address && fault_address < mm.address.1);
+
+        if fault_address == instruction_pointer
+            && let Some(mm) = containing
+            && !mm.perms.contains(MMPermissions::EXECUTE)
+        {
+            diagnosis.class = FaultClass::NonExecutableFetch;
+            diagnosis.mapping = Some(mm.into());
+            return Ok(Some(diagnosis));
+        }
+
+        // Like `get_stack_info`, skip over guard pages (mappings with no
+        // access) looking for a stack within the guard gap above the fault.
+        let may_be_stack = |mm: &MemoryMap| {
+            mm.perms
+                .intersects(MMPermissions::READ | MMPermissions::WRITE)
+        };
+        if containing.is_none_or(|mm| !may_be_stack(mm)) {
+            let gap_end = fault_address.saturating_add(STACK_GUARD_GAP as u64);
+            let stack = maps
+                .iter()
+                .filter(|mm| mm.address.0 > fault_address && mm.address.0 <= gap_end)
+                .find(|mm| may_be_stack(mm));
+
+            // Only blame a stack overflow if the crashing thread was actually
+            // running on that stack.
+            if let Some(stack) = stack
+                && stack_pointer < stack.address.1
+                && stack_pointer.saturating_add(STACK_GUARD_GAP as u64) >= stack.address.0
+            {
+                diagnosis.class = FaultClass::StackOverflow;
+                diagnosis.distance_below_stack = Some(stack.address.0 - fault_address);
+                diagnosis.mapping = Some(stack.into());
+            }
+        }
+
+        Ok(Some(diagnosis))
+    }
+
+    /// Writes [`Self::fault_diagnosis`], if the fault was diagnosed
+    pub fn write_fault_diagnosis_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionFaultDiagnosisError> {
+        let Some(diagnosis) = &self.fault_diagnosis else {
+            return Ok(Default::default());
+        };
+
+        let location = write_json(buffer, diagnosis)
+            .map_err(SectionFaultDiagnosisError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxFaultDiagnosis as u32,
+            location,
+        })
+    }
+}
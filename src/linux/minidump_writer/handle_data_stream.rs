@@ -70,6 +70,7 @@ impl MinidumpWriter {
             .read_dir(&proc_fd_path)
             .map_err(SectionHandleDataStreamError::ReadDirFailed)?;
         let descriptors: Vec<_> = proc_fd_iter
+            .take_while(|_| !self.budget.exceeded())
             .filter_map(|filename| filename.ok())
             .filter_map(|filename| {
                 let path = proc_fd_path.join(filename);
@@ -0,0 +1,242 @@
+use {
+    super::*,
+    crate::{
+        linux::maps_reader::{MappingEntry, SystemMappingInfo},
+        module_reader::ProcessModuleMemoryReader,
+    },
+    std::borrow::Cow,
+};
+
+/// The symbol through which GDB's JIT interface finds the code objects a JIT
+/// has registered, see
+/// <https://sourceware.org/gdb/current/onlinedocs/gdb.html/JIT-Interface.html>
+const JIT_DESCRIPTOR_SYMBOL: &str = "__jit_debug_descriptor";
+const JIT_DESCRIPTOR_VERSION: u32 = 1;
+
+/// Limits that keep a corrupt or very large list of registered objects from
+/// blowing up the minidump. The entry limit also ends the walk if the list
+/// contains a cycle.
+const MAX_JIT_ENTRIES: usize = 4096;
+const MAX_JIT_SYMFILE_SIZE: usize = 16 * 1024 * 1024;
+const MAX_JIT_CODE_SIZE: usize = 64 * 1024 * 1024;
+
+// COPY from the GDB documentation
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct JitDescriptor {
+    version: u32,
+    action_flag: u32,
+    relevant_entry: usize, // WAS: `struct jit_code_entry *`
+    first_entry: usize,    // WAS: `struct jit_code_entry *`
+}
+
+// COPY from the GDB documentation
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct JitCodeEntry {
+    next_entry: usize,   // WAS: `struct jit_code_entry *`
+    prev_entry: usize,   // WAS: `struct jit_code_entry *`
+    symfile_addr: usize, // WAS: `const char *`
+    symfile_size: u64,
+}
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionJitCodeError {
+    #[error("Failed to copy the JIT descriptor or code entries from the process")]
+    CopyFromProcessError(#[from] CopyFromProcessError),
+    #[error("Unsupported JIT interface version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Too many JIT code entries, the rest were skipped")]
+    TooManyEntries,
+    #[error("JIT object at {address:#x} ({size} bytes) exceeds the size limit")]
+    SymfileTooLarge { address: usize, size: u64 },
+    #[error("Failed to copy JIT object at {address:#x}")]
+    CopySymfileFailed {
+        address: usize,
+        #[source]
+        error: CopyFromProcessError,
+    },
+    #[error("JIT object at {address:#x} is not a loaded ELF object")]
+    InvalidSymfile {
+        address: usize,
+        #[source]
+        error: module_reader::ModuleReaderError,
+    },
+}
+
+impl MinidumpWriter {
+    /// Finds the code objects registered through the GDB JIT interface, and
+    /// adds a module for the code each one describes so that frames in JIT
+    /// code can be symbolicated. Only the headers of the objects are read, they
+    /// are copied into the memory list by [`Self::write_jit_code`].
+    ///
+    /// This does nothing unless enabled with
+    /// [`MinidumpWriterConfig::capture_jit_code`], and must be called before
+    /// the module list stream is written.
+    pub fn find_jit_code(&mut self, mut soft_errors: impl WriteErrorList<SectionJitCodeError>) {
+        if !self.capture_jit_code {
+            return;
+        }
+
+        for (_, address) in self.find_exported_symbol(JIT_DESCRIPTOR_SYMBOL) {
+            if let Err(e) = self.find_jit_code_entries(address, &mut soft_errors) {
+                soft_errors.push(e);
+            }
+        }
+    }
+
+    fn find_jit_code_entries(
+        &mut self,
+        descriptor_address: usize,
+        soft_errors: &mut impl WriteErrorList<SectionJitCodeError>,
+    ) -> Result<(), SectionJitCodeError> {
+        let reader = self.process_inspector.process_reader();
+
+        // SAFETY: the descriptor only holds integers
+        let descriptor: JitDescriptor = unsafe { reader.copy_object(descriptor_address) }?;
+        if descriptor.version != JIT_DESCRIPTOR_VERSION {
+            return Err(SectionJitCodeError::UnsupportedVersion(descriptor.version));
+        }
+
+        let mut total_size = self.jit_objects.iter().map(|(_, size)| size).sum::<usize>();
+        let mut entry_address = descriptor.first_entry;
+        for _ in 0..MAX_JIT_ENTRIES {
+            if entry_address == 0 || self.budget.exceeded() {
+                return Ok(());
+            }
+            // SAFETY: the entry only holds integers
+            let entry: JitCodeEntry = unsafe { reader.copy_object(entry_address) }?;
+            entry_address = entry.next_entry;
+
+            let address = entry.symfile_addr;
+            let size = match usize::try_from(entry.symfile_size) {
+                Ok(0) => continue,
+                Ok(size)
+                    if size <= MAX_JIT_SYMFILE_SIZE && total_size + size <= MAX_JIT_CODE_SIZE =>
+                {
+                    size
+                }
+                _ => {
+                    soft_errors.push(SectionJitCodeError::SymfileTooLarge {
+                        address,
+                        size: entry.symfile_size,
+                    });
+                    continue;
+                }
+            };
+            total_size += size;
+            self.jit_objects.push((address, size));
+
+            let symfile = JitObjectMemory {
+                memory: ProcessModuleMemoryReader::new(&reader, address),
+                address,
+                size: size as u64,
+            };
+            match jit_module(&symfile, address) {
+                Ok(module) => self.jit_modules.push(module),
+                Err(error) => {
+                    soft_errors.push(SectionJitCodeError::InvalidSymfile { address, error })
+                }
+            }
+        }
+
+        if entry_address != 0 {
+            soft_errors.push(SectionJitCodeError::TooManyEntries);
+        }
+        Ok(())
+    }
+
+    /// Copies the code objects found by [`Self::find_jit_code`] into the
+    /// memory list, until the budget of the stream runs out.
+    pub fn write_jit_code(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionJitCodeError>,
+    ) {
+        let reader = self.process_inspector.process_reader();
+
+        for &(address, size) in &self.jit_objects {
+            if self.budget.exceeded() {
+                break;
+            }
+            let symfile = match reader.read_all_to_vec(address, size) {
+                Ok(symfile) => symfile,
+                Err(error) => {
+                    soft_errors.push(SectionJitCodeError::CopySymfileFailed { address, error });
+                    continue;
+                }
+            };
+
+            let section = MemoryArrayWriter::write_bytes(buffer, &symfile);
+            self.memory_blocks.push(MDMemoryDescriptor {
+                start_of_memory_range: address as u64,
+                memory: section.location(),
+            });
+        }
+    }
+}
+
+/// A JIT object in the memory of the process, which is an ELF file image
+/// rather than a loaded one
+struct JitObjectMemory<'a> {
+    memory: ProcessModuleMemoryReader<'a>,
+    address: usize,
+    size: u64,
+}
+
+impl module_reader::ReadModuleMemory for JitObjectMemory<'_> {
+    fn read(
+        &self,
+        offset: u64,
+        length: u64,
+    ) -> Result<Cow<'_, [u8]>, module_reader::ModuleMemoryReadError> {
+        if offset.checked_add(length).is_none_or(|end| end > self.size) {
+            return Err(module_reader::ModuleMemoryReadError {
+                start_address: Some(self.address as u64),
+                offset,
+                length,
+                error: module_reader::ReadError::OutOfBounds,
+            });
+        }
+        self.memory.read(offset, length)
+    }
+    fn absolute_to_relative(&self, addr: u64) -> Option<u64> {
+        Some(addr)
+    }
+    fn relative_to_absolute(&self, addr: u64) -> Option<u64> {
+        Some(addr)
+    }
+    fn is_process_memory(&self) -> bool {
+        false
+    }
+}
+
+/// Describes the code of a JIT object as a module, named after the address of
+/// the object so it can be found in the memory list.
+fn jit_module(
+    symfile: &JitObjectMemory<'_>,
+    symfile_address: usize,
+) -> Result<MappingEntry, module_reader::ModuleReaderError> {
+    let range = module_reader::ModuleReader::new(symfile)?.loaded_address_range()?;
+    let identifier = module_reader::read_build_id_from_module(symfile).unwrap_or_else(|e| {
+        log::warn!("failed to get build id for JIT object at {symfile_address:#x}: {e}");
+        Vec::new()
+    });
+
+    let start_address = range.start as usize;
+    let end_address = range.end as usize;
+    Ok(MappingEntry {
+        mapping: MappingInfo {
+            start_address,
+            size: end_address - start_address,
+            system_mapping_info: SystemMappingInfo {
+                start_address,
+                end_address,
+            },
+            offset: 0,
+            permissions: MMPermissions::READ | MMPermissions::EXECUTE,
+            name: Some(format!("[jit:{symfile_address:#x}]").into()),
+        },
+        identifier,
+    })
+}
@@ -1,4 +1,7 @@
-use super::{super::maps_reader::MappingInfo, *};
+use super::{
+    super::maps_reader::{MappingEntry, MappingInfo},
+    *,
+};
 
 #[derive(Debug, Error, serde::Serialize)]
 pub enum SectionMappingsError {
@@ -18,6 +21,7 @@ impl MinidumpWriter {
         buffer: &mut DumpBuf,
     ) -> Result<MDRawDirectory, SectionMappingsError> {
         let mut modules = Vec::new();
+        let mut written = std::collections::HashSet::new();
 
         // First write all the mappings from the dumper
         for map_idx in 0..self.mappings.len() {
@@ -41,10 +45,13 @@ impl MinidumpWriter {
                 let Some(path) = &self.mappings[map_idx].name else {
                     return Err(e);
                 };
+                if self.budget.exceeded() {
+                    return Err(e);
+                }
 
                 log::debug!("failed to get build id from process memory ({e}), attempting to retrieve from {}", path.display());
 
-                module_reader::read_build_id_from_file(&self.process_inspector, path.as_ref()).map_err(errors::WriterError::ModuleReaderError)
+                module_reader::read_build_id_from_file(&self.process_inspector, &self.module_files, &self.mappings[map_idx]).map_err(errors::WriterError::ModuleReaderError)
             })
             .unwrap_or_else(|e| {
                 log::warn!("failed to get build id for mapping: {e}");
@@ -62,12 +69,38 @@ impl MinidumpWriter {
 
             let module = fill_raw_module(
                 &self.process_inspector,
+                &self.module_files,
                 buffer,
                 &self.mappings[map_idx],
                 &identifier,
                 soname,
             )?;
             modules.push(module);
+            written.insert(map_idx);
+        }
+
+        // Then executable mappings that have no module of their own, if asked to
+        if self.synthesize_anonymous_modules {
+            for (map_idx, mapping) in self.mappings.iter().enumerate() {
+                if written.contains(&map_idx) || mapping.is_contained_in(&self.user_mapping_list) {
+                    continue;
+                }
+                if self.budget.exceeded() {
+                    break;
+                }
+                let Some(synthetic) = self.synthetic_module(mapping) else {
+                    continue;
+                };
+                let module = fill_raw_module(
+                    &self.process_inspector,
+                    &self.module_files,
+                    buffer,
+                    &synthetic.mapping,
+                    &synthetic.identifier,
+                    synthetic_module_file_name(&synthetic.mapping),
+                )?;
+                modules.push(module);
+            }
         }
 
         // Next write all the mappings provided by the caller
@@ -75,6 +108,7 @@ impl MinidumpWriter {
             // GUID was provided by caller.
             let module = fill_raw_module(
                 &self.process_inspector,
+                &self.module_files,
                 buffer,
                 &user.mapping,
                 &user.identifier,
@@ -83,6 +117,23 @@ impl MinidumpWriter {
             modules.push(module);
         }
 
+        // And finally the code objects registered by JITs, whose names are
+        // already what they should be called
+        for jit in &self.jit_modules {
+            let module = fill_raw_module(
+                &self.process_inspector,
+                &self.module_files,
+                buffer,
+                &jit.mapping,
+                &jit.identifier,
+                jit.mapping
+                    .name
+                    .as_ref()
+                    .map(|n| n.to_string_lossy().into_owned()),
+            )?;
+            modules.push(module);
+        }
+
         let list_header = MemoryWriter::<u32>::alloc_with_val(buffer, modules.len() as u32)?;
 
         let mut dirent = MDRawDirectory {
@@ -98,8 +149,65 @@ impl MinidumpWriter {
         Ok(dirent)
     }
 }
+impl MinidumpWriter {
+    /// Describes an anonymous or `memfd` executable mapping as a module, see
+    /// [`MinidumpWriterConfig::synthesize_anonymous_modules`].
+    ///
+    /// Mappings that contain JIT code objects are left alone, as those are
+    /// already described by more precise modules.
+    fn synthetic_module(&self, mapping: &MappingInfo) -> Option<MappingEntry> {
+        if !mapping.is_executable() {
+            return None;
+        }
+        let name = match mapping.name.as_deref().map(|n| n.as_encoded_bytes()) {
+            None => format!("[anon:exec:{:#x}]", mapping.start_address).into(),
+            Some(n) if n.starts_with(b"[anon:") => {
+                format!("[anon:exec:{:#x}]", mapping.start_address).into()
+            }
+            Some(n) if n.starts_with(b"/memfd:") => mapping.name.clone()?,
+            Some(_) => return None,
+        };
+
+        let range = &mapping.system_mapping_info;
+        if self.jit_modules.iter().any(|jit| {
+            jit.mapping.start_address < range.end_address
+                && range.start_address < jit.mapping.end_address()
+        }) {
+            return None;
+        }
+
+        // Like the fallback for modules without a build id, hash the first page
+        let length = std::cmp::min(mapping.size, 4096);
+        let first_page =
+            Self::copy_from_process(&self.process_inspector, mapping.start_address, length)
+                .inspect_err(|e| {
+                    log::warn!(
+                        "failed to read first page of executable mapping at {:#x}: {e}",
+                        mapping.start_address
+                    )
+                })
+                .ok()?;
+
+        Some(MappingEntry {
+            mapping: MappingInfo {
+                name: Some(name),
+                ..mapping.clone()
+            },
+            identifier: module_reader::build_id_from_bytes(&first_page),
+        })
+    }
+}
+
+/// The name to use in place of a SONAME for a synthetic module, which keeps
+/// the module name from being looked up in the file system.
+fn synthetic_module_file_name(mapping: &MappingInfo) -> Option<String> {
+    let name = std::path::Path::new(mapping.name.as_deref()?);
+    Some(name.file_name()?.to_string_lossy().into_owned())
+}
+
 fn fill_raw_module(
     process_inspector: &ProcessInspector,
+    module_files: &module_reader::ModuleFiles,
     buffer: &mut DumpBuf,
     mapping: &MappingInfo,
     identifier: &[u8],
@@ -125,7 +233,7 @@ fn fill_raw_module(
     };
 
     let (file_path, _, so_version) = mapping
-        .get_mapping_effective_path_name_and_version(process_inspector, soname)
+        .get_mapping_effective_path_name_and_version(process_inspector, module_files, soname)
         .map_err(|e| SectionMappingsError::GetEffectivePathError(mapping.clone(), e))?;
     let name_header = write_string_to_location(buffer, file_path.to_string_lossy().as_ref())?;
 
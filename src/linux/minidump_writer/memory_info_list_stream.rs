@@ -1,15 +1,15 @@
 use {
     super::*,
     minidump_common::format::{MemoryProtection, MemoryState, MemoryType},
-    procfs_core::{FromRead, process::MMPermissions},
+    procfs_core::process::{MMPermissions, MMapPath, MemoryMap},
 };
 
 #[derive(Debug, Error, serde::Serialize)]
 pub enum SectionMemInfoListError {
     #[error("Failed to write to memory")]
     MemoryWriterError(#[from] MemoryWriterError),
-    #[error("failed to open /proc/<pid>/maps file")]
-    ReadFileFailed(#[source] process_inspection::Error),
+    #[error("the maps of the process couldn't be read")]
+    MapsUnavailable,
     #[error("Failed to read from procfs")]
     ProcfsError(
         #[from]
@@ -19,24 +19,30 @@ pub enum SectionMemInfoListError {
 }
 
 impl MinidumpWriter {
-    /// Write a MemoryInfoListStream using information from procfs.
+    /// Write a MemoryInfoListStream from the maps of the process.
+    ///
+    /// The regions of a mapped file share the allocation base of its module,
+    /// and the inaccessible guard below a thread's stack is marked as a
+    /// reserved guard page that the stack is allocated from. Other
+    /// inaccessible regions are reserved address space, unless smaps shows
+    /// that they hold pages.
     pub fn write_memory_info_list_stream(
         &mut self,
         buffer: &mut DumpBuf,
     ) -> Result<MDRawDirectory, SectionMemInfoListError> {
-        let path = format!("/proc/{}/maps", self.blamed_thread);
-        let reader = self
-            .process_inspector
-            .read_file(&path)
-            .map_err(SectionMemInfoListError::ReadFileFailed)?;
-        let maps = procfs_core::process::MemoryMaps::from_read(reader)?;
+        let maps = self
+            .maps
+            .as_ref()
+            .ok_or(SectionMemInfoListError::MapsUnavailable)?
+            .memory_maps()?;
+        let regions = maps.iter().as_slice();
 
         let list_header = MemoryWriter::alloc_with_val(
             buffer,
             MDMemoryInfoList {
                 size_of_header: std::mem::size_of::<MDMemoryInfoList>() as u32,
                 size_of_entry: std::mem::size_of::<MDMemoryInfo>() as u32,
-                number_of_entries: maps.len() as u64,
+                number_of_entries: regions.len() as u64,
             },
         )?;
 
@@ -45,16 +51,38 @@ impl MinidumpWriter {
             location: list_header.location(),
         };
 
-        let block_list = MemoryArrayWriter::<MDMemoryInfo>::alloc_from_iter(
-            buffer,
-            maps.iter().map(|mm| MDMemoryInfo {
+        let mut infos = Vec::with_capacity(regions.len());
+        let mut stack_guard = None;
+        for (idx, mm) in regions.iter().enumerate() {
+            let allocation_protection = get_memory_protection(mm.perms);
+            let mut protection = allocation_protection;
+            let mut state = MemoryState::MEM_COMMIT;
+            let allocation_base = stack_guard
+                .take()
+                .or_else(|| self.file_allocation_base(mm))
+                .unwrap_or(mm.address.0);
+
+            if protection == MemoryProtection::PAGE_NOACCESS {
+                let below_stack = regions
+                    .get(idx + 1)
+                    .is_some_and(|next| next.address.0 == mm.address.1 && self.is_stack(next));
+                if below_stack {
+                    stack_guard = Some(mm.address.0);
+                    protection |= MemoryProtection::PAGE_GUARD;
+                    state = MemoryState::MEM_RESERVE;
+                } else if !holds_pages(mm) {
+                    state = MemoryState::MEM_RESERVE;
+                }
+            }
+
+            infos.push(MDMemoryInfo {
                 base_address: mm.address.0,
-                allocation_base: mm.address.0,
-                allocation_protection: get_memory_protection(mm.perms).bits(),
+                allocation_base,
+                allocation_protection: allocation_protection.bits(),
                 __alignment1: 0,
                 region_size: mm.address.1 - mm.address.0,
-                state: MemoryState::MEM_COMMIT.bits(),
-                protection: get_memory_protection(mm.perms).bits(),
+                state: state.bits(),
+                protection: protection.bits(),
                 _type: if mm.perms.contains(MMPermissions::PRIVATE) {
                     MemoryType::MEM_PRIVATE
                 } else {
@@ -62,14 +90,49 @@ impl MinidumpWriter {
                 }
                 .bits(),
                 __alignment2: 0,
-            }),
-        )?;
+            });
+        }
+
+        let block_list = MemoryArrayWriter::<MDMemoryInfo>::alloc_from_iter(buffer, infos)?;
 
         dirent.location.data_size += block_list.location().data_size;
 
         Ok(dirent)
     }
+
+    /// The start of the module the region is a mapping of a file of, if any
+    fn file_allocation_base(&self, mm: &MemoryMap) -> Option<u64> {
+        let start = usize::try_from(mm.address.0).ok()?;
+        self.mappings
+            .iter()
+            .find(|mapping| {
+                mapping.name_is_path()
+                    && (mapping.system_mapping_info.start_address
+                        ..mapping.system_mapping_info.end_address)
+                        .contains(&start)
+            })
+            .map(|mapping| mapping.system_mapping_info.start_address as u64)
+    }
+
+    /// Whether the region is the main stack, or holds the stack pointer of a
+    /// thread
+    fn is_stack(&self, mm: &MemoryMap) -> bool {
+        matches!(mm.pathname, MMapPath::Stack | MMapPath::TStack(_))
+            || self
+                .stack_pointers
+                .iter()
+                .any(|&sp| (mm.address.0..mm.address.1).contains(&(sp as u64)))
+    }
 }
+
+/// Whether smaps shows that the region holds resident or swapped out pages.
+/// Without smaps, this is unknown and assumed not to be the case.
+fn holds_pages(mm: &MemoryMap) -> bool {
+    ["Rss", "Swap"]
+        .iter()
+        .any(|key| mm.extension.map.get(*key).is_some_and(|&bytes| bytes > 0))
+}
+
 fn get_memory_protection(permissions: MMPermissions) -> MemoryProtection {
     let read = permissions.contains(MMPermissions::READ);
     let write = permissions.contains(MMPermissions::WRITE);
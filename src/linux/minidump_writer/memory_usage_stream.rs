@@ -0,0 +1,89 @@
+use {super::*, procfs_core::process::MemoryMap};
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionMemoryUsageError {
+    #[error("Failed to read from procfs")]
+    ProcfsError(
+        #[from]
+        #[serde(serialize_with = "serialize_proc_error")]
+        procfs_core::ProcError,
+    ),
+    #[error("Failed to convert the memory usage to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+/// How many bytes of memory are resident, swapped out and anonymous
+#[derive(Debug, Default, serde::Serialize)]
+struct Usage {
+    rss: u64,
+    swap: u64,
+    /// Resident bytes that aren't backed by a file, which includes the
+    /// modified copy-on-write pages of private file mappings
+    anonymous: u64,
+}
+
+impl From<&MemoryMap> for Usage {
+    fn from(mm: &MemoryMap) -> Self {
+        let field = |key: &str| mm.extension.map.get(key).copied().unwrap_or_default();
+        Self {
+            rss: field("Rss"),
+            swap: field("Swap"),
+            anonymous: field("Anonymous"),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Region {
+    /// The base address of the region in the memory info list
+    base_address: u64,
+    #[serde(flatten)]
+    usage: Usage,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct MemoryUsage {
+    total: Usage,
+    regions: Vec<Region>,
+}
+
+impl MinidumpWriter {
+    /// Writes the usage of each region of the memory info list.
+    ///
+    /// This is only written if smaps were captured, see
+    /// [`MinidumpWriterConfig::capture_smaps`].
+    pub fn write_memory_usage_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionMemoryUsageError> {
+        let Some(maps) = self.maps.as_ref().filter(|maps| maps.smaps) else {
+            return Ok(Default::default());
+        };
+
+        let mut usage = MemoryUsage {
+            total: Usage::default(),
+            regions: Vec::new(),
+        };
+        for mm in maps.memory_maps()?.iter() {
+            let region = Usage::from(mm);
+            usage.total.rss += region.rss;
+            usage.total.swap += region.swap;
+            usage.total.anonymous += region.anonymous;
+            usage.regions.push(Region {
+                base_address: mm.address.0,
+                usage: region,
+            });
+        }
+
+        let location =
+            write_json(buffer, &usage).map_err(SectionMemoryUsageError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxMemoryUsage as u32,
+            location,
+        })
+    }
+}
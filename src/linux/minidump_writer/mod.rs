@@ -6,8 +6,10 @@ use {
         crash_context_ext::CrashContextExt,
         dso_debug,
         dumper_cpu_info::CpuInfoError,
-        maps_reader::{MappingInfo, MappingList, MapsReaderError},
-        process_inspection::{self, ProcessInspector, process_reader::CopyFromProcessError},
+        maps_reader::{MappingInfo, MappingList, MapsReaderError, MapsSnapshot},
+        process_inspection::{
+            self, PreopenedDirs, ProcessInspector, process_reader::CopyFromProcessError,
+        },
         serializers::*,
         thread_info::{ThreadInfo, ThreadInfoError},
     },
@@ -28,6 +30,8 @@ use {
         process::{MMPermissions, ProcState, Stat},
     },
     std::{
+        cell::Cell,
+        collections::{HashMap, HashSet},
         io::{Read, Seek, Write},
         time::{Duration, Instant},
     },
@@ -37,23 +41,50 @@ use {
 #[cfg(target_os = "android")]
 use super::android::late_process_mappings;
 
-pub use super::auxv::{AuxvType, DirectAuxvDumpInfo};
+pub use {
+    super::auxv::{AuxvType, DirectAuxvDumpInfo},
+    exception_stream::RequestedException,
+    perf_map_stream::PerfMapOptions,
+};
 
+pub mod abort_message_stream;
+pub mod annotations_stream;
 pub mod app_memory;
+pub mod container_stream;
+pub mod debug_info_stream;
+pub mod degraded_stream;
 pub mod errors;
 pub mod exception_stream;
+pub mod exited_threads_stream;
+pub mod fault_diagnosis_stream;
 pub mod handle_data_stream;
+pub mod jit_code;
 pub mod mappings;
 pub mod memory_info_list_stream;
 pub mod memory_list_stream;
+pub mod memory_usage_stream;
+pub mod module_files_stream;
+pub mod perf_map_stream;
+pub mod siginfo_stream;
+pub mod signal_context_stream;
+pub mod symbol_hints_stream;
 pub mod systeminfo_stream;
 pub mod thread_list_stream;
 pub mod thread_names_stream;
+pub mod watchdog;
 
 /// The default timeout after a `SIGSTOP` after which minidump writing proceeds
 /// regardless of the process state
 pub const STOP_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// How many times the threads of the process are listed again to find those it
+/// created while the ones listed before were being suspended
+pub const MAX_THREAD_RESCANS: usize = 8;
+
+/// The size of the gap the kernel keeps below a stack. It has been 1 MiB since
+/// kernel 4.12, older kernels used a single 4 KiB guard page instead.
+pub const STACK_GUARD_GAP: usize = 1024 * 1024;
+
 #[cfg(target_pointer_width = "32")]
 pub const AT_SYSINFO_EHDR: u32 = 33;
 #[cfg(target_pointer_width = "64")]
@@ -71,9 +102,21 @@ pub struct MinidumpWriterConfig {
     memory_blocks: Vec<MDMemoryDescriptor>,
     principal_mapping: Option<MappingInfo>,
     sanitize_stack: bool,
+    capture_jit_code: bool,
+    perf_map_options: Option<PerfMapOptions>,
+    synthesize_anonymous_modules: bool,
+    capture_gnu_debugdata: bool,
+    capture_symbol_hints: bool,
+    capture_smaps: bool,
+    no_ptrace: bool,
+    threads_already_traced: bool,
     crash_context: Option<CrashContextExt>,
+    requested_exception: Option<RequestedException>,
     crashing_thread_context: CrashingThreadContext,
     stop_timeout: Duration,
+    watchdog_deadline: Option<Duration>,
+    deadline: Option<Duration>,
+    stream_budgets: HashMap<u32, Duration>,
     direct_auxv_dump_info: Option<DirectAuxvDumpInfo>,
     process_inspector: ProcessInspector,
 }
@@ -81,30 +124,121 @@ pub struct MinidumpWriterConfig {
 #[derive(Debug)]
 pub struct MinidumpWriter {
     pub process_id: Pid,
+    /// The id of the process in each PID namespace it is in, like
+    /// [`Thread::namespace_tids`]
+    pub namespace_pids: Vec<Pid>,
     threads_suspended: bool,
     pub threads: Vec<Thread>,
+    /// Threads that were listed but couldn't be captured as they exited
+    pub exited_threads: Vec<ExitedThread>,
+    /// How many times the threads were listed again after suspending them
+    pub thread_rescans: usize,
+    /// Whether the suspended threads matched the listed ones in the end
+    pub thread_set_stable: bool,
     pub auxv: AuxvDumpInfo,
     pub mappings: Vec<MappingInfo>,
+    /// The maps of the process, read once its threads are suspended, which the
+    /// mappings, the memory info list and the maps stream are derived from
+    pub maps: Option<MapsSnapshot>,
     pub page_size: usize,
     pub sanitize_stack: bool,
+    pub capture_jit_code: bool,
+    pub perf_map_options: Option<PerfMapOptions>,
+    pub synthesize_anonymous_modules: bool,
+    pub capture_gnu_debugdata: bool,
+    pub capture_symbol_hints: bool,
+    pub capture_smaps: bool,
+    pub no_ptrace: bool,
+    pub threads_already_traced: bool,
     pub minidump_size_limit: Option<u64>,
     pub user_mapping_list: MappingList,
+    /// Modules for the code objects registered through the GDB JIT interface
+    pub jit_modules: MappingList,
+    /// The address and size of each of those objects, copied into the memory
+    /// list after the module list is written
+    pub jit_objects: Vec<(usize, usize)>,
+    /// Where the files of the modules in `mappings` can be opened from
+    pub module_files: module_reader::ModuleFiles,
     pub crashing_thread_context: CrashingThreadContext,
     stop_timeout: Duration,
+    watchdog_deadline: Option<Duration>,
+    watchdog: Option<watchdog::Watchdog>,
+    deadline: Option<Instant>,
+    stream_budgets: HashMap<u32, Duration>,
+    /// The budget of the stream being written
+    pub budget: Budget,
     pub skip_stacks_if_mapping_unreferenced: bool,
     principal_mapping_address: Option<usize>,
     pub principal_mapping: Option<MappingInfo>,
     pub blamed_thread: Pid,
     pub crash_context: Option<CrashContextExt>,
+    /// The fault described by the crash context, diagnosed once for both the
+    /// exception stream and the fault diagnosis stream
+    pub fault_diagnosis: Option<fault_diagnosis_stream::FaultDiagnosis>,
+    pub requested_exception: Option<RequestedException>,
     pub app_memory: AppMemoryList,
     pub memory_blocks: Vec<MDMemoryDescriptor>,
+    /// The stack pointers of the dumped threads, which tell their stacks apart
+    /// from other memory
+    pub stack_pointers: Vec<usize>,
     pub process_inspector: ProcessInspector,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct Thread {
     pub tid: Pid,
     pub name: Option<String>,
+    /// The id of the thread in each PID namespace it is in, from the
+    /// dumper's outward to the innermost, or empty if the kernel doesn't
+    /// report it
+    pub namespace_tids: Vec<Pid>,
+}
+
+/// The time the stream being written has left, see
+/// [`MinidumpWriterConfig::deadline`] and [`MinidumpWriterConfig::stream_budget`]
+#[derive(Debug, Default)]
+pub struct Budget {
+    end: Option<Instant>,
+    exceeded: Cell<bool>,
+}
+
+impl Budget {
+    fn new(deadline: Option<Instant>, budget: Option<Duration>) -> Self {
+        let end = budget.map(|budget| Instant::now() + budget);
+        Self {
+            end: match (deadline, end) {
+                (Some(deadline), Some(end)) => Some(deadline.min(end)),
+                (deadline, end) => deadline.or(end),
+            },
+            exceeded: Default::default(),
+        }
+    }
+
+    /// Whether the stream is out of time and should be finished with the data
+    /// it already has
+    pub fn exceeded(&self) -> bool {
+        if self.end.is_some_and(|end| Instant::now() >= end) {
+            self.exceeded.set(true);
+        }
+        self.exceeded.get()
+    }
+}
+
+/// A thread that was listed, but exited before its state could be captured
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExitedThread {
+    #[serde(flatten)]
+    pub thread: Thread,
+    pub exited: ThreadExit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadExit {
+    /// The thread was gone when it was to be suspended
+    BeforeSuspension,
+    /// The thread was suspended, but killed before all threads were
+    AfterSuspension,
 }
 
 #[derive(Debug, Default)]
@@ -128,14 +262,33 @@ impl MinidumpWriterConfig {
             memory_blocks: Default::default(),
             principal_mapping: Default::default(),
             sanitize_stack: Default::default(),
+            capture_jit_code: Default::default(),
+            perf_map_options: Default::default(),
+            synthesize_anonymous_modules: Default::default(),
+            capture_gnu_debugdata: Default::default(),
+            capture_symbol_hints: Default::default(),
+            capture_smaps: Default::default(),
+            no_ptrace: Default::default(),
+            threads_already_traced: Default::default(),
             crash_context: Default::default(),
+            requested_exception: Default::default(),
             crashing_thread_context: Default::default(),
             stop_timeout: STOP_TIMEOUT,
+            watchdog_deadline: Default::default(),
+            deadline: Default::default(),
+            stream_budgets: Default::default(),
             direct_auxv_dump_info: Default::default(),
             process_inspector: ProcessInspector::local(process_id),
         }
     }
 
+    /// Makes the writer access the filesystem only through the given directories, for dumpers
+    /// that can't open absolute paths once confined
+    pub fn preopened_dirs(&mut self, dirs: PreopenedDirs) -> &mut Self {
+        self.process_inspector = ProcessInspector::local_with_preopened_dirs(self.process_id, dirs);
+        self
+    }
+
     pub fn set_minidump_size_limit(&mut self, limit: u64) -> &mut Self {
         self.minidump_size_limit = Some(limit);
         self
@@ -161,6 +314,15 @@ impl MinidumpWriterConfig {
         self
     }
 
+    /// Sets the exception to record when dumping a process that didn't crash,
+    /// so that eg. hangs and assertion failures can be told apart.
+    ///
+    /// This is ignored if a crash context is set.
+    pub fn set_requested_exception(&mut self, exception: RequestedException) -> &mut Self {
+        self.requested_exception = Some(exception);
+        self
+    }
+
     pub fn skip_stacks_if_mapping_unreferenced(&mut self) -> &mut Self {
         self.skip_stacks_if_mapping_unreferenced = true; // Off by default
         self
@@ -171,6 +333,89 @@ impl MinidumpWriterConfig {
         self
     }
 
+    /// Copies the code objects JIT compilers register through the GDB JIT
+    /// interface (`__jit_debug_descriptor`) into the minidump, and adds a module
+    /// for each one.
+    ///
+    /// The descriptor must be exported in the dynamic symbol table of the
+    /// executable or a shared library for it to be found.
+    pub fn capture_jit_code(&mut self) -> &mut Self {
+        self.capture_jit_code = true; // Off by default
+        self
+    }
+
+    /// Adds modules for executable mappings that would otherwise have none:
+    /// anonymous mappings, named `[anon:exec:<start>]`, and mappings of
+    /// `memfd`s without a build id, named after the `memfd`.
+    ///
+    /// Their identifier is a hash of their first page, so frames in them can at
+    /// least be attributed to a region.
+    pub fn synthesize_anonymous_modules(&mut self) -> &mut Self {
+        self.synthesize_anonymous_modules = true; // Off by default
+        self
+    }
+
+    /// Copies the `.gnu_debugdata` section ("MiniDebugInfo") of each module
+    /// into the minidump. Distributions such as Fedora ship it so that
+    /// backtraces can be symbolicated without the debug package.
+    pub fn capture_gnu_debugdata(&mut self) -> &mut Self {
+        self.capture_gnu_debugdata = true; // Off by default
+        self
+    }
+
+    /// Never attaches to the process with ptrace, for hosts that forbid it.
+    ///
+    /// Memory is still read with `process_vm_readv` or `/proc/<pid>/mem`, but
+    /// of the registers of each thread, only the stack and instruction pointers
+    /// are known, from procfs. The dump is marked as degraded in the
+    /// [`MDExtStreamType::LinuxDegradedDump`] stream.
+    pub fn no_ptrace(&mut self) -> &mut Self {
+        self.no_ptrace = true; // Off by default
+        self
+    }
+
+    /// Declares that the calling thread already traces all threads of the
+    /// process with ptrace, and that they are stopped, e.g. because the dumper
+    /// supervises the process.
+    ///
+    /// The writer then neither stops the process nor attaches to or detaches
+    /// from its threads, and leaves them traced and stopped, but still reads
+    /// their registers with ptrace. It must therefore run on the tracing thread.
+    pub fn threads_already_traced(&mut self) -> &mut Self {
+        self.threads_already_traced = true; // Off by default
+        self
+    }
+
+    /// Records the nearest exported function of each thread's instruction
+    /// pointer and of each code address on the crashing thread's stack, so
+    /// that frames in modules without symbol files can be named after a
+    /// dynamic symbol.
+    pub fn capture_symbol_hints(&mut self) -> &mut Self {
+        self.capture_symbol_hints = true; // Off by default
+        self
+    }
+
+    /// Reads `/proc/<pid>/smaps` rather than `/proc/<pid>/maps`, which also
+    /// tells how much of each mapping is resident or swapped out, but takes
+    /// the kernel longer to produce as it walks the page tables.
+    ///
+    /// The memory info list then tells inaccessible regions that hold pages
+    /// from merely reserved ones, and the usage of each region is written to
+    /// the [`MDExtStreamType::LinuxMemoryUsage`] stream.
+    pub fn capture_smaps(&mut self) -> &mut Self {
+        self.capture_smaps = true; // Off by default
+        self
+    }
+
+    /// Captures the entries of the process's `/tmp/perf-<pid>.map`, which JITs
+    /// such as the JVM (with perf-map-agent), Node (`--perf-basic-prof`) and
+    /// .NET write to name their generated code, that lie in anonymous
+    /// executable mappings.
+    pub fn capture_perf_map(&mut self, options: PerfMapOptions) -> &mut Self {
+        self.perf_map_options = Some(options); // Off by default
+        self
+    }
+
     /// Sets the timeout after `SIGSTOP` is sent to the process, if the process
     /// has not stopped by the time the timeout has reached, we proceed with
     /// minidump generation
@@ -179,6 +424,46 @@ impl MinidumpWriterConfig {
         self
     }
 
+    /// Forks a sentinel process that continues the process if the dumper dies
+    /// while the process is stopped, e.g. because it crashed or was killed by
+    /// the OOM killer.
+    ///
+    /// **Once `deadline` has passed since the writer started, the sentinel
+    /// kills the calling process with `SIGKILL`**, as only it can detach from
+    /// the threads, and then continues the dumped process, whatever the state
+    /// of the dump. Only use this in a dumper process that may be killed.
+    ///
+    /// The writer refuses to start if this is combined with
+    /// [`Self::threads_already_traced`], as the caller's own tracing would be
+    /// ended along with it.
+    pub fn watchdog(&mut self, deadline: Duration) -> &mut Self {
+        self.watchdog_deadline = Some(deadline); // Off by default
+        self
+    }
+
+    /// Bounds the time writing the minidump may take, from the call to
+    /// [`Self::write`].
+    ///
+    /// Once the deadline has passed, the stream being written is finished with
+    /// the data it already has and the remaining streams are skipped, each
+    /// with a soft error. The streams needed to process the minidump, i.e. the
+    /// thread list, module list, memory list, exception and system info
+    /// streams, are never skipped, but threads other than the crashing one are
+    /// then written without their stack, and modules whose build id isn't in
+    /// memory are left out.
+    pub fn deadline(&mut self, deadline: Duration) -> &mut Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Bounds the time writing the stream `stream_type`, an [`MDStreamType`] or
+    /// [`MDExtStreamType`], may take, like [`Self::deadline`] does for the
+    /// whole minidump.
+    pub fn stream_budget(&mut self, stream_type: u32, budget: Duration) -> &mut Self {
+        self.stream_budgets.insert(stream_type, budget);
+        self
+    }
+
     /// Directly set important Auxv info determined by the crashing process
     ///
     /// Since `/proc/{pid}/auxv` can sometimes be inaccessible, the calling process should prefer to transfer this
@@ -222,23 +507,47 @@ impl MinidumpWriterConfig {
 
         MinidumpWriter {
             process_id: self.process_id,
+            namespace_pids: Default::default(),
             threads_suspended: Default::default(),
             threads: Default::default(),
+            exited_threads: Default::default(),
+            thread_rescans: Default::default(),
+            thread_set_stable: Default::default(),
             auxv,
             mappings: Default::default(),
+            maps: Default::default(),
             page_size: Default::default(),
             sanitize_stack: self.sanitize_stack,
+            capture_jit_code: self.capture_jit_code,
+            perf_map_options: self.perf_map_options,
+            synthesize_anonymous_modules: self.synthesize_anonymous_modules,
+            capture_gnu_debugdata: self.capture_gnu_debugdata,
+            capture_symbol_hints: self.capture_symbol_hints,
+            capture_smaps: self.capture_smaps,
+            no_ptrace: self.no_ptrace,
+            threads_already_traced: self.threads_already_traced,
             minidump_size_limit: self.minidump_size_limit,
             user_mapping_list: self.user_mapping_list,
+            jit_modules: Default::default(),
+            jit_objects: Default::default(),
+            module_files: Default::default(),
             crashing_thread_context: self.crashing_thread_context,
             stop_timeout: self.stop_timeout,
+            watchdog_deadline: self.watchdog_deadline,
+            watchdog: Default::default(),
+            deadline: self.deadline.map(|deadline| Instant::now() + deadline),
+            stream_budgets: self.stream_budgets,
+            budget: Default::default(),
             skip_stacks_if_mapping_unreferenced: self.skip_stacks_if_mapping_unreferenced,
             principal_mapping_address: self.principal_mapping_address,
             principal_mapping: self.principal_mapping,
             blamed_thread: self.blamed_thread,
             crash_context: self.crash_context,
+            fault_diagnosis: Default::default(),
+            requested_exception: self.requested_exception,
             app_memory: self.app_memory,
             memory_blocks: self.memory_blocks,
+            stack_pointers: Default::default(),
             process_inspector: self.process_inspector,
         }
     }
@@ -251,8 +560,27 @@ impl MinidumpWriter {
             return Err(InitError::CannotPtraceSameProcess);
         }
 
+        match self.process_inspector.open_process() {
+            Ok(()) => {}
+            Err(e) if e.is_process_gone() => return Err(InitError::ProcessGone),
+            // The process can still be addressed by its pid
+            Err(e) => soft_errors.push(InitError::OpenProcessFailed(e)),
+        }
+
+        if let Some(deadline) = self.watchdog_deadline {
+            if self.threads_already_traced {
+                return Err(InitError::WatchdogWithThreadsAlreadyTraced);
+            }
+            self.watchdog = Some(
+                watchdog::Watchdog::spawn(self.process_id, deadline)
+                    .map_err(InitError::StartWatchdogFailed)?,
+            );
+        }
+
         // Stopping the process is best-effort.
-        if let Err(e) = self.stop_process(self.stop_timeout) {
+        if !self.threads_already_traced
+            && let Err(e) = self.stop_process(self.stop_timeout)
+        {
             soft_errors.push(InitError::StopProcessFailed(e));
         }
 
@@ -274,10 +602,11 @@ impl MinidumpWriter {
             soft_errors.push(InitError::EnumerateThreadsFailed(Box::new(e)));
         }
 
-        // Same with mappings -- Some information is still better than no information!
-        if let Err(e) = self.enumerate_mappings() {
-            soft_errors.push(InitError::EnumerateMappingsFailed(Box::new(e)));
+        match self.read_namespace_ids(format!("/proc/{}/status", self.process_id)) {
+            Ok(ids) => self.namespace_pids = ids,
+            Err(e) => soft_errors.push(InitError::ReadNamespaceIdsFailed(e)),
         }
+        self.translate_blamed_thread();
 
         self.page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE).try_into().unwrap() };
         assert!(
@@ -285,12 +614,24 @@ impl MinidumpWriter {
             "somehow we weren't able to get the page size - should never happen"
         );
 
-        let threads_count = self.threads.len();
+        if !self.no_ptrace && !self.threads_already_traced {
+            let threads_count = self.threads.len();
+            let listed = self.threads.iter().map(|thread| thread.tid).collect();
 
-        self.suspend_threads(soft_errors.subwriter(InitError::SuspendThreadsErrors));
+            self.suspend_threads(0, soft_errors.subwriter(InitError::SuspendThreadsErrors));
+
+            if self.threads.is_empty() {
+                soft_errors.push(InitError::SuspendNoThreadsLeft(threads_count));
+            }
 
-        if self.threads.is_empty() {
-            soft_errors.push(InitError::SuspendNoThreadsLeft(threads_count));
+            self.stabilize_threads(listed, &mut soft_errors);
+        }
+
+        // The maps are only read once the threads are suspended, so that they
+        // can't change anymore. Some information is still better than no
+        // information!
+        if let Err(e) = self.enumerate_mappings() {
+            soft_errors.push(InitError::EnumerateMappingsFailed(Box::new(e)));
         }
 
         #[cfg(target_os = "android")]
@@ -320,7 +661,7 @@ impl MinidumpWriter {
     ) -> Result<(), WriterError> {
         // A minidump file contains a number of tagged streams. This is the number
         // of streams which we write.
-        let num_writers = 18u32;
+        let num_writers = 32u32;
 
         let mut header_section = MemoryWriter::<MDRawHeader>::alloc(buffer)?;
 
@@ -345,42 +686,303 @@ impl MinidumpWriter {
         // we should have a mostly-intact dump
         dir_section.write_to_file(buffer, None)?;
 
-        let dirent = self.write_thread_list_stream(buffer)?;
+        // Writes a stream within its time budget, skipping it if it has none
+        // left unless it must always be written
+        macro_rules! budgeted {
+            (always $stream_type:expr, $write:expr) => {{
+                let stream_type = $stream_type as u32;
+                self.budget = Budget::new(
+                    self.deadline,
+                    self.stream_budgets.get(&stream_type).copied(),
+                );
+                let written = $write;
+                if self.budget.exceeded.get() {
+                    soft_errors.push(WriterError::StreamBudgetExceeded(stream_type));
+                }
+                written
+            }};
+            ($stream_type:expr, $write:expr) => {{
+                let stream_type = $stream_type as u32;
+                self.budget = Budget::new(
+                    self.deadline,
+                    self.stream_budgets.get(&stream_type).copied(),
+                );
+                if self.budget.exceeded() {
+                    soft_errors.push(WriterError::StreamSkipped(stream_type));
+                    Default::default()
+                } else {
+                    let written = $write;
+                    if self.budget.exceeded.get() {
+                        soft_errors.push(WriterError::StreamBudgetExceeded(stream_type));
+                    }
+                    written
+                }
+            }};
+        }
+
+        let dirent = budgeted!(
+            always MDStreamType::ThreadListStream,
+            self.write_thread_list_stream(buffer)?
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        let dirent = self.write_mappings(buffer)?;
+        let dirent = budgeted!(always MDStreamType::ModuleListStream, {
+            self.find_jit_code(soft_errors.subwriter(WriterError::WriteJitCodeErrors));
+            self.write_mappings(buffer)?
+        });
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        self.write_app_memory(buffer)
-            .map_err(WriterError::SectionAppMemoryError)?;
+        // The memory list itself only refers to memory already written, so it
+        // is always written, unlike JIT code and app memory
+        budgeted!(MDStreamType::MemoryListStream, {
+            self.write_jit_code(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteJitCodeErrors),
+            );
+            self.write_app_memory(buffer)
+                .map_err(WriterError::SectionAppMemoryError)?
+        });
         dir_section.write_to_file(buffer, None)?;
 
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxSignalContext,
+            match self.write_signal_context_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteSignalContextErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteSignalContextStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
         let dirent = self.write_memory_list_stream(buffer)?;
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        let dirent = self.write_exception_stream(buffer)?;
+        if let Some(requested) = &self.requested_exception
+            && requested.parameters.len() > exception_stream::MAX_EXCEPTION_PARAMETERS
+        {
+            soft_errors.push(WriterError::TooManyExceptionParameters(
+                requested.parameters.len(),
+            ));
+        }
+        match self.diagnose_fault() {
+            Ok(diagnosis) => self.fault_diagnosis = diagnosis,
+            Err(e) => soft_errors.push(WriterError::DiagnoseFaultFailed(e)),
+        }
+        let dirent = budgeted!(
+            always MDStreamType::ExceptionStream,
+            self.write_exception_stream(buffer)?
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDStreamType::CommentStreamA,
+            self.write_comment_stream(buffer)?
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        let dirent = systeminfo_stream::write(
-            &self.process_inspector,
-            buffer,
-            soft_errors.subwriter(WriterError::WriteSystemInfoErrors),
-        )?;
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxFaultDiagnosis,
+            match self.write_fault_diagnosis_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteFaultDiagnosisStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxSigInfo,
+            match self.write_siginfo_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteSigInfoStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxAbortMessage,
+            match self.write_abort_message_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteAbortMessageStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxPerfMap,
+            match self.write_perf_map_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WritePerfMapErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WritePerfMapStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDStreamType::CrashpadInfoStream,
+            match self.write_annotations_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteAnnotationsErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteAnnotationsStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxDebugInfo,
+            match self.write_debug_info_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteDebugInfoErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteDebugInfoStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxContainer,
+            match self.write_container_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteContainerErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteContainerStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxDegradedDump,
+            match self.write_degraded_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteDegradedErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteDegradedStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxExitedThreads,
+            match self.write_exited_threads_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteExitedThreadsStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxModuleFiles,
+            match self.write_module_files_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteModuleFilesErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteModuleFilesStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxSymbolHints,
+            match self.write_symbol_hints_stream(
+                buffer,
+                soft_errors.subwriter(WriterError::WriteSymbolHintsErrors),
+            ) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteSymbolHintsStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        let dirent = self.write_memory_info_list_stream(buffer)?;
+        let dirent = budgeted!(
+            always MDStreamType::SystemInfoStream,
+            systeminfo_stream::write(
+                &self.process_inspector,
+                buffer,
+                soft_errors.subwriter(WriterError::WriteSystemInfoErrors),
+            )?
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
+        let dirent = budgeted!(
+            MDStreamType::MemoryInfoListStream,
+            self.write_memory_info_list_stream(buffer)?
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        let dirent = budgeted!(
+            MDExtStreamType::LinuxMemoryUsage,
+            match self.write_memory_usage_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteMemoryUsageStreamFailed(e));
+                    Default::default()
+                }
+            }
+        );
+        dir_section.write_to_file(buffer, Some(dirent))?;
+
+        // Through the process's directory, so that the files are read through
+        // the one opened by `ProcessInspector::open_process`
         let mut proc_root = {
-            let mut pr = String::with_capacity(24);
+            let mut pr = String::with_capacity(40);
             use std::fmt::Write;
-            write!(&mut pr, "/proc/{}/", self.blamed_thread).unwrap(); // infallbile barring OOM
+            write!(
+                &mut pr,
+                "/proc/{}/task/{}/",
+                self.process_id, self.blamed_thread
+            )
+            .unwrap(); // infallbile barring OOM
             pr
         };
 
         macro_rules! file_entry {
             (res $write:expr, $kind:ident, $err:ident) => {
-                let dirent = match $write {
+                let dirent = budgeted!(MDStreamType::$kind, match $write {
                     Ok(location) => MDRawDirectory {
                         stream_type: MDStreamType::$kind as u32,
                         location,
@@ -389,7 +991,7 @@ impl MinidumpWriter {
                         soft_errors.push(WriterError::$err(e));
                         Default::default()
                     }
-                };
+                });
                 dir_section.write_to_file(buffer, Some(dirent))?;
             };
             ($fname:literal, $kind:ident, $err:ident) => {
@@ -424,30 +1026,38 @@ impl MinidumpWriter {
         file_entry!("cmdline", LinuxCmdLine, WriteCommandLineFailed);
         file_entry!("environ", LinuxEnviron, WriteEnvironmentFailed);
         file_entry!("auxv", LinuxAuxv, WriteEnvironmentFailed);
-        file_entry!("maps", LinuxMaps, WriteMapsFailed);
+        file_entry!(res self.write_maps(buffer), LinuxMaps, WriteMapsFailed);
 
-        let dirent =
+        let dirent = budgeted!(
+            MDStreamType::LinuxDsoDebug,
             match dso_debug::write_dso_debug_stream(&self.process_inspector, buffer, &self.auxv) {
                 Ok(dirent) => dirent,
                 Err(e) => {
                     soft_errors.push(WriterError::WriteDSODebugStreamFailed(e));
                     Default::default()
                 }
-            };
+            }
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
         file_entry!("limits", MozLinuxLimits, WriteLimitsFailed);
 
-        let dirent = self.write_thread_names_stream(buffer)?;
+        let dirent = budgeted!(
+            MDStreamType::ThreadNamesStream,
+            self.write_thread_names_stream(buffer)?
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
-        let dirent = match self.write_handle_data_stream(buffer) {
-            Ok(dirent) => dirent,
-            Err(e) => {
-                soft_errors.push(WriterError::WriteHandleDataStreamFailed(e));
-                Default::default()
+        let dirent = budgeted!(
+            MDStreamType::HandleDataStream,
+            match self.write_handle_data_stream(buffer) {
+                Ok(dirent) => dirent,
+                Err(e) => {
+                    soft_errors.push(WriterError::WriteHandleDataStreamFailed(e));
+                    Default::default()
+                }
             }
-        };
+        );
         dir_section.write_to_file(buffer, Some(dirent))?;
 
         // If this fails, there's really nothing we can do about that (other than ignore it).
@@ -522,6 +1132,18 @@ impl MinidumpWriter {
         process_inspector
             .suspend_thread(tid)
             .map_err(WriterError::SuspendThreadFailed)?;
+        Self::check_suspended_thread(process_inspector, tid)
+    }
+
+    /// Resumes a suspended thread again if it is of no use in the minidump
+    #[cfg_attr(
+        not(any(target_arch = "x86", target_arch = "x86_64")),
+        allow(unused_variables)
+    )]
+    fn check_suspended_thread(
+        process_inspector: &ProcessInspector,
+        tid: Pid,
+    ) -> Result<(), WriterError> {
         #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
         {
             // On x86, the stack pointer is NULL or -1, when executing trusted code in
@@ -562,26 +1184,107 @@ impl MinidumpWriter {
             .map_err(WriterError::ResumeThreadFailed)
     }
 
-    fn suspend_threads(&mut self, mut soft_errors: impl WriteErrorList<WriterError>) {
-        // Iterate over all threads and try to suspend them.
+    /// Suspends the threads from index `first` on.
+    fn suspend_threads(&mut self, first: usize, mut soft_errors: impl WriteErrorList<WriterError>) {
+        // Ask every thread to stop before waiting for any of them, so that
+        // they stop concurrently rather than one after the other.
+        let mut threads = self.threads.split_off(first);
+        let mut interrupted = threads
+            .iter()
+            .map(|x| self.process_inspector.interrupt_thread(x.tid))
+            .collect::<Vec<_>>()
+            .into_iter();
+
         // If the thread either disappeared before we could attach to it, or if
         // it was part of the seccomp sandbox's trusted code, it is OK to
         // silently drop it from the minidump.
-        self.threads.retain(
-            |x| match Self::suspend_thread(&self.process_inspector, x.tid) {
+        let mut exited = Vec::new();
+        threads.retain(|x| {
+            let result = match interrupted.next().unwrap() {
+                Ok(()) => self
+                    .process_inspector
+                    .wait_for_interrupt(x.tid)
+                    .map_err(WriterError::SuspendThreadFailed)
+                    .and_then(|()| Self::check_suspended_thread(&self.process_inspector, x.tid)),
+                // e.g. because the kernel predates PTRACE_SEIZE
+                Err(e) if e.is_seize_failure() => {
+                    Self::suspend_thread(&self.process_inspector, x.tid)
+                }
+                Err(e) => Err(WriterError::SuspendThreadFailed(e)),
+            };
+            match result {
                 Ok(()) => true,
+                Err(_) if !self.thread_exists(x.tid) => {
+                    exited.push(ExitedThread {
+                        thread: x.clone(),
+                        exited: ThreadExit::BeforeSuspension,
+                    });
+                    false
+                }
                 Err(e) => {
                     soft_errors.push(e);
                     false
                 }
-            },
-        );
+            }
+        });
+        self.threads.extend(threads);
+        self.exited_threads.extend(exited);
 
         self.threads_suspended = true;
 
         failspot::failspot!(<crate::FailSpotName>::SuspendThreads soft_errors.push(WriterError::PtraceAttachError(1234, libc::EPERM)))
     }
 
+    /// Lists the threads again until they match the suspended ones, suspending
+    /// the threads that aren't in `listed`, the threads
+    /// [`Self::enumerate_threads`] found, and dropping those that are gone. New
+    /// threads show up if stopping the process failed, or if they were being
+    /// created while it stopped.
+    fn stabilize_threads(
+        &mut self,
+        mut listed: HashSet<Pid>,
+        mut soft_errors: impl WriteErrorList<InitError>,
+    ) {
+        for _ in 0..MAX_THREAD_RESCANS {
+            let tids = match self.read_thread_ids(&mut soft_errors) {
+                Ok(tids) => tids,
+                Err(e) => {
+                    soft_errors.push(e);
+                    return;
+                }
+            };
+            self.thread_rescans += 1;
+
+            // Suspended threads can still be killed
+            let (threads, gone): (Vec<_>, Vec<_>) = std::mem::take(&mut self.threads)
+                .into_iter()
+                .partition(|thread| tids.contains(&thread.tid));
+            self.threads = threads;
+            self.exited_threads
+                .extend(gone.into_iter().map(|thread| ExitedThread {
+                    thread,
+                    exited: ThreadExit::AfterSuspension,
+                }));
+
+            let new_tids: Vec<Pid> = tids.into_iter().filter(|&tid| listed.insert(tid)).collect();
+            if new_tids.is_empty() {
+                self.thread_set_stable = true;
+                return;
+            }
+
+            let first = self.threads.len();
+            for tid in new_tids {
+                let thread = self.read_thread(tid, &mut soft_errors);
+                self.threads.push(thread);
+            }
+            self.suspend_threads(
+                first,
+                soft_errors.subwriter(InitError::SuspendThreadsErrors),
+            );
+        }
+        soft_errors.push(InitError::ThreadSetUnstable(MAX_THREAD_RESCANS));
+    }
+
     fn resume_threads(&mut self, mut soft_errors: impl WriteErrorList<WriterError>) {
         if self.threads_suspended {
             for thread in &self.threads {
@@ -641,8 +1344,27 @@ impl MinidumpWriter {
         &mut self,
         mut soft_errors: impl WriteErrorList<InitError>,
     ) -> Result<(), InitError> {
-        let pid = self.process_id;
-        let task_path = format!("/proc/{pid}/task");
+        for tid in self.read_thread_ids(&mut soft_errors)? {
+            let thread = self.read_thread(tid, &mut soft_errors);
+            self.threads.push(thread);
+        }
+
+        Ok(())
+    }
+
+    fn thread_exists(&self, tid: Pid) -> bool {
+        self.process_inspector
+            .stat_file(format!("/proc/{}/task/{tid}", self.process_id))
+            .is_ok()
+    }
+
+    /// Lists the ids of the threads in /proc/$pid/task
+    fn read_thread_ids(
+        &self,
+        soft_errors: &mut impl WriteErrorList<InitError>,
+    ) -> Result<Vec<Pid>, InitError> {
+        let task_path = format!("/proc/{}/task", self.process_id);
+        let mut tids = Vec::new();
 
         for file_name in self
             .process_inspector
@@ -656,41 +1378,103 @@ impl MinidumpWriter {
                     continue;
                 }
             };
-            let tid = match file_name.to_str().and_then(|name| name.parse::<Pid>().ok()) {
-                Some(tid) => tid,
-                None => {
-                    soft_errors.push(InitError::ProcessTaskEntryNotTid(file_name));
-                    continue;
-                }
-            };
-
-            if failspot!(ThreadName) {
-                self.process_inspector.fail_one_syscall_with(libc::EPERM);
+            match file_name.to_str().and_then(|name| name.parse::<Pid>().ok()) {
+                Some(tid) => tids.push(tid),
+                None => soft_errors.push(InitError::ProcessTaskEntryNotTid(file_name)),
             }
+        }
 
-            // Read the thread-name (if there is any)
-            let name_result = self
-                .process_inspector
-                .read_file(format!("/proc/{pid}/task/{tid}/comm"))
-                .map_err(std::io::Error::other)
-                .and_then(|mut file| {
-                    let mut s = String::new();
-                    file.read_to_string(&mut s)?;
-                    Ok(s)
-                });
+        Ok(tids)
+    }
 
-            let name = match name_result {
-                Ok(name) => Some(name.trim_end().to_string()),
-                Err(e) => {
-                    soft_errors.push(InitError::ReadThreadNameFailed(e));
-                    None
-                }
-            };
+    /// Reads the name and namespace ids of the thread `tid`
+    fn read_thread(&self, tid: Pid, soft_errors: &mut impl WriteErrorList<InitError>) -> Thread {
+        let pid = self.process_id;
 
-            self.threads.push(Thread { tid, name });
+        if failspot!(ThreadName) {
+            self.process_inspector.fail_one_syscall_with(libc::EPERM);
         }
 
-        Ok(())
+        // Read the thread-name (if there is any)
+        let name_result = self
+            .process_inspector
+            .read_file(format!("/proc/{pid}/task/{tid}/comm"))
+            .map_err(std::io::Error::other)
+            .and_then(|mut file| {
+                let mut s = String::new();
+                file.read_to_string(&mut s)?;
+                Ok(s)
+            });
+
+        // Threads that exited meanwhile are accounted for when suspending them
+        let name = match name_result {
+            Ok(name) => Some(name.trim_end().to_string()),
+            Err(e) if self.thread_exists(tid) => {
+                soft_errors.push(InitError::ReadThreadNameFailed(e));
+                None
+            }
+            Err(_) => None,
+        };
+
+        let namespace_tids = match self.read_namespace_ids(format!("/proc/{pid}/task/{tid}/status"))
+        {
+            Ok(ids) => ids,
+            Err(e) if self.thread_exists(tid) => {
+                soft_errors.push(InitError::ReadNamespaceIdsFailed(e));
+                Vec::new()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        Thread {
+            tid,
+            name,
+            namespace_tids,
+        }
+    }
+
+    /// Reads the `NSpid` line of a `/proc/<pid>/status` or
+    /// `/proc/<pid>/task/<tid>/status` file, which lists the id in each PID
+    /// namespace, from that of the procfs mount to the innermost.
+    fn read_namespace_ids(&self, path: String) -> Result<Vec<Pid>, std::io::Error> {
+        let mut status = String::new();
+        self.process_inspector
+            .read_file(path)
+            .map_err(std::io::Error::other)?
+            .read_to_string(&mut status)?;
+
+        // Kernels before 4.1 don't have the line
+        Ok(status
+            .lines()
+            .find_map(|line| line.strip_prefix("NSpid:"))
+            .map(|ids| {
+                ids.split_whitespace()
+                    .filter_map(|id| id.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// The crashing process reports its thread id in its own PID namespace,
+    /// which is a different thread, or none at all, from outside a container.
+    /// If the blamed thread isn't a thread of the process, but is the
+    /// namespace-local id of one, use that thread's id instead.
+    fn translate_blamed_thread(&mut self) {
+        if self.threads.iter().any(|t| t.tid == self.blamed_thread) {
+            return;
+        }
+        if let Some(thread) = self
+            .threads
+            .iter()
+            .find(|t| t.namespace_tids.last() == Some(&self.blamed_thread))
+        {
+            log::info!(
+                "translated blamed thread {} from the process's PID namespace to {}",
+                self.blamed_thread,
+                thread.tid
+            );
+            self.blamed_thread = thread.tid;
+        }
     }
 
     fn enumerate_mappings(&mut self) -> Result<(), InitError> {
@@ -701,12 +1485,17 @@ impl MinidumpWriter {
         // case its entry when creating the list of mappings.
         // See http://www.trilithium.com/johan/2005/08/linux-gate/ for more
         // information.
-        self.mappings = MappingInfo::for_pid(
-            &self.process_inspector,
-            self.process_id,
-            self.auxv.get_linux_gate_address(),
-        )
-        .map_err(InitError::AggregateMappingsFailed)?;
+        let maps = self.maps.insert(
+            MapsSnapshot::read(&self.process_inspector, self.process_id, self.capture_smaps)
+                .map_err(InitError::AggregateMappingsFailed)?,
+        );
+        self.mappings = maps
+            .memory_maps()
+            .map_err(MapsReaderError::from)
+            .and_then(|maps| MappingInfo::aggregate(maps, self.auxv.get_linux_gate_address()))
+            .map_err(InitError::AggregateMappingsFailed)?;
+        self.module_files =
+            module_reader::ModuleFiles::new(&self.process_inspector, self.process_id);
 
         // Although the initial executable is usually the first mapping, it's not
         // guaranteed (see http://crosbug.com/25355); therefore, try to use the
@@ -731,6 +1520,17 @@ impl MinidumpWriter {
         Ok(())
     }
 
+    /// Writes the maps the mappings were derived from, which may differ from
+    /// what `/proc/<pid>/maps` reads by now
+    fn write_maps(&self, buffer: &mut DumpBuf) -> Result<MDLocationDescriptor, MemoryWriterError> {
+        let maps = self
+            .maps
+            .as_ref()
+            .ok_or_else(|| std::io::Error::other("the maps of the process couldn't be read"))?;
+        let section = MemoryArrayWriter::write_bytes(buffer, &maps.maps());
+        Ok(section.location())
+    }
+
     /// Read thread info from /proc/$pid/status.
     /// Fill out the |tgid|, |ppid| and |pid| members of |info|. If unavailable,
     /// these members are set to -1. Returns true if all three members are
@@ -740,7 +1540,17 @@ impl MinidumpWriter {
             return Err(ThreadInfoError::IndexOutOfBounds(index, self.threads.len()));
         }
 
-        ThreadInfo::create(&self.process_inspector, self.threads[index].tid)
+        self.thread_info(self.threads[index].tid)
+    }
+
+    /// Reads the info of a thread of the process, through ptrace unless
+    /// disabled with [`MinidumpWriterConfig::no_ptrace`].
+    pub fn thread_info(&self, tid: Pid) -> Result<ThreadInfo, ThreadInfoError> {
+        if self.no_ptrace {
+            ThreadInfo::create_without_ptrace(&self.process_inspector, self.process_id, tid)
+        } else {
+            ThreadInfo::create(&self.process_inspector, tid)
+        }
     }
 
     // Returns a valid stack pointer and the mapping that contains the stack.
@@ -753,10 +1563,9 @@ impl MinidumpWriter {
         let mut stack_pointer = int_stack_pointer & !(self.page_size - 1);
         let mut mapping = self.find_mapping(stack_pointer);
 
-        // The guard page has been 1 MiB in size since kernel 4.12, older
-        // kernels used a 4 KiB one instead. Note the saturating add, as 32-bit
-        // processes can have a stack pointer within 1MiB of usize::MAX
-        let guard_page_max_addr = stack_pointer.saturating_add(1024 * 1024);
+        // Note the saturating add, as 32-bit processes can have a stack pointer
+        // within 1MiB of usize::MAX
+        let guard_page_max_addr = stack_pointer.saturating_add(STACK_GUARD_GAP);
 
         // If we found no mapping, or the mapping we found has no permissions
         // then we might have hit a guard page, try looking for a mapping in
@@ -941,6 +1750,46 @@ impl MinidumpWriter {
         .map_err(WriterError::ModuleReaderError)
     }
 
+    /// The instruction pointers of all threads, with the crash address in
+    /// addition for the blamed thread.
+    fn thread_instruction_pointers(&self) -> Vec<u64> {
+        let mut ips: Vec<u64> = self
+            .threads
+            .iter()
+            .filter_map(|t| self.thread_info(t.tid).ok())
+            .map(|info| info.get_instruction_pointer() as u64)
+            .collect();
+        if let Some(crash_context) = &self.crash_context {
+            ips.push(crash_context.get_instruction_pointer() as u64);
+        }
+        ips
+    }
+
+    /// Finds a symbol in the dynamic symbol tables of the mapped modules,
+    /// returning the index of each mapping that defines it along with the
+    /// symbol's address in the process.
+    ///
+    /// Modules whose dynamic symbols can't be read are skipped, as are mappings
+    /// that aren't ELF files at all.
+    pub fn find_exported_symbol(&self, name: &str) -> Vec<(usize, usize)> {
+        use module_reader::ReadModuleMemory;
+
+        let reader = self.process_inspector.process_reader();
+        self.mappings
+            .iter()
+            .enumerate()
+            .filter(|(_, mapping)| mapping.is_interesting())
+            .filter_map(|(idx, mapping)| {
+                let module_memory =
+                    module_reader::ProcessModuleMemoryReader::new(&reader, mapping.start_address);
+                let symbol =
+                    module_reader::find_dynamic_symbol_in_module(&module_memory, name).ok()??;
+                let address = module_memory.relative_to_absolute(symbol.address)?;
+                Some((idx, address as usize))
+            })
+            .collect()
+    }
+
     /// Copies a block of bytes from the target process, returning the heap
     /// allocated copy
     #[inline]
@@ -960,8 +1809,13 @@ impl Drop for MinidumpWriter {
     fn drop(&mut self) {
         // Always try to resume all threads (e.g. in case of error)
         self.resume_threads(error_graph::strategy::DontCare);
-        // Always allow the process to continue.
-        let _ = self.continue_process();
+        // Always allow the process to continue, unless it is up to its tracer.
+        if !self.threads_already_traced {
+            let _ = self.continue_process();
+        }
+        if let Some(watchdog) = self.watchdog.take() {
+            watchdog.disarm();
+        }
     }
 }
 
@@ -983,6 +1837,15 @@ fn write_file(
     Ok(section.location())
 }
 
+fn write_json(
+    buffer: &mut DumpBuf,
+    value: &impl serde::Serialize,
+) -> Result<MDLocationDescriptor, serde_json::Error> {
+    let json = serde_json::to_vec_pretty(value)?;
+    let section = MemoryArrayWriter::write_bytes(buffer, &json);
+    Ok(section.location())
+}
+
 fn write_soft_errors(
     buffer: &mut DumpBuf,
     soft_errors: ErrorList<WriterError>,
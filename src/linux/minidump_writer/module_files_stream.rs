@@ -0,0 +1,167 @@
+use {super::*, module_reader::ModuleFileSource};
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionModuleFilesError {
+    #[error("Failed to stat the mapped file of {module}")]
+    StatMappedFileFailed {
+        module: String,
+        #[source]
+        error: process_inspection::Error,
+    },
+    #[error("Failed to stat {module}")]
+    StatFileFailed {
+        module: String,
+        #[source]
+        error: process_inspection::Error,
+    },
+    #[error("Failed to convert the module file metadata to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+/// The identity and metadata of a file, from `stat`
+#[derive(Debug, serde::Serialize)]
+struct FileMetadata {
+    /// `st_dev`
+    device: u64,
+    inode: u64,
+    size: i64,
+    /// The modification time in seconds since the epoch
+    mtime: i64,
+    mtime_nsec: i64,
+}
+
+impl From<libc::stat> for FileMetadata {
+    // The fields are narrower on some 32-bit architectures
+    #[allow(clippy::useless_conversion)]
+    fn from(stat: libc::stat) -> Self {
+        Self {
+            device: u64::from(stat.st_dev),
+            inode: u64::from(stat.st_ino),
+            size: i64::from(stat.st_size),
+            mtime: i64::from(stat.st_mtime),
+            mtime_nsec: i64::from(stat.st_mtime_nsec),
+        }
+    }
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ModuleFile {
+    module: String,
+    base_address: u64,
+    /// The file that is actually mapped, if `/proc/<pid>/map_files` could be
+    /// read
+    mapped: Option<FileMetadata>,
+    /// The file currently at the module's path in the process's mount
+    /// namespace, if there is one
+    on_disk: Option<FileMetadata>,
+    /// Where the module's file is read from when its build id or SONAME can't
+    /// be read from memory, see [`module_reader::ModuleFiles`]
+    source: Option<ModuleFileSource>,
+    /// Whether the mapped file was unlinked, which is also the case if another
+    /// file was renamed over it, or there is no file at the path
+    deleted: bool,
+    /// Whether the path now refers to a different file than the mapped one,
+    /// e.g. after an upgrade, or `None` if that can't be told
+    replaced: Option<bool>,
+}
+
+impl MinidumpWriter {
+    /// Writes the device, inode, size and modification time of the file each
+    /// module was mapped from, and of the file now at its path, so that
+    /// modules that were deleted or replaced on disk since they were loaded
+    /// can be detected.
+    pub fn write_module_files_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionModuleFilesError>,
+    ) -> Result<MDRawDirectory, SectionModuleFilesError> {
+        let mut map_files_usable = true;
+
+        let mut modules = Vec::new();
+        for mapping in &self.mappings {
+            if !mapping.is_interesting() {
+                continue;
+            }
+            let Some(path) = mapping.name.as_deref().map(std::path::Path::new) else {
+                continue;
+            };
+            if !path.is_absolute() {
+                continue;
+            }
+            let module = path.to_string_lossy().into_owned();
+
+            let mapped = match self.module_files.map_files_path(mapping) {
+                Some(map_file) if map_files_usable => {
+                    match self.process_inspector.stat_file(map_file) {
+                        Ok(stat) => Some(stat),
+                        Err(error) => {
+                            // Following the links needs CAP_SYS_ADMIN, so if
+                            // one fails the rest will too
+                            soft_errors.push(SectionModuleFilesError::StatMappedFileFailed {
+                                module: module.clone(),
+                                error,
+                            });
+                            map_files_usable = false;
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+            let root_path = self
+                .module_files
+                .root_path(mapping)
+                .unwrap_or_else(|| path.to_owned());
+            let on_disk = match self.process_inspector.stat_file(root_path) {
+                Ok(stat) => Some(stat),
+                Err(e) if e.is_not_found() => None,
+                Err(error) => {
+                    soft_errors.push(SectionModuleFilesError::StatFileFailed {
+                        module: module.clone(),
+                        error,
+                    });
+                    None
+                }
+            };
+            let source = if mapped.is_some() {
+                Some(ModuleFileSource::MapFiles)
+            } else if on_disk.is_some() {
+                Some(ModuleFileSource::ProcessRoot)
+            } else if self.process_inspector.stat_file(path).is_ok() {
+                Some(ModuleFileSource::Path)
+            } else {
+                None
+            };
+
+            let deleted = mapped.is_some_and(|m| m.st_nlink == 0) || on_disk.is_none();
+            let replaced = match (&mapped, &on_disk) {
+                (Some(m), Some(d)) => Some(m.st_dev != d.st_dev || m.st_ino != d.st_ino),
+                _ => None,
+            };
+            modules.push(ModuleFile {
+                module,
+                base_address: mapping.start_address as u64,
+                mapped: mapped.map(Into::into),
+                on_disk: on_disk.map(Into::into),
+                source,
+                deleted,
+                replaced,
+            });
+        }
+
+        if modules.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let location =
+            write_json(buffer, &modules).map_err(SectionModuleFilesError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxModuleFiles as u32,
+            location,
+        })
+    }
+}
@@ -0,0 +1,135 @@
+use {
+    super::*,
+    std::io::{BufRead, BufReader},
+};
+
+/// Which entries of the perf map to keep, see
+/// [`MinidumpWriterConfig::capture_perf_map`].
+#[derive(Debug, Clone)]
+pub struct PerfMapOptions {
+    /// Only keep the entries within this many bytes of the instruction pointer
+    /// of a thread. If `None`, all entries in anonymous executable mappings are
+    /// kept.
+    pub ip_window: Option<u64>,
+    /// The most bytes of entries to write, the remaining entries are dropped
+    pub max_size: usize,
+}
+
+impl Default for PerfMapOptions {
+    fn default() -> Self {
+        Self {
+            ip_window: None,
+            max_size: 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionPerfMapError {
+    #[error("Failed to open the perf map")]
+    ReadFileFailed(#[source] process_inspection::Error),
+    #[error("Failed to read the perf map")]
+    IoError(
+        #[source]
+        #[serde(serialize_with = "serialize_io_error")]
+        std::io::Error,
+    ),
+    #[error("The perf map entries exceeded {0} bytes, the rest were dropped")]
+    Truncated(usize),
+}
+
+/// Parses a perf map line: the start address and size in hex, followed by the
+/// name, which may contain spaces.
+fn parse_perf_map_line(line: &str) -> Option<(u64, u64)> {
+    let mut parts = line.splitn(3, ' ');
+    let start = parts.next()?;
+    let size = parts.next()?;
+    parts.next()?;
+    let start = u64::from_str_radix(start.trim_start_matches("0x"), 16).ok()?;
+    let size = u64::from_str_radix(size.trim_start_matches("0x"), 16).ok()?;
+    Some((start, size))
+}
+
+impl MinidumpWriter {
+    /// Writes the entries of the perf map that JITs write for `perf` that
+    /// describe code in anonymous executable mappings.
+    ///
+    /// This does nothing unless enabled with
+    /// [`MinidumpWriterConfig::capture_perf_map`], or if the process hasn't
+    /// written a perf map.
+    pub fn write_perf_map_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionPerfMapError>,
+    ) -> Result<MDRawDirectory, SectionPerfMapError> {
+        let Some(options) = &self.perf_map_options else {
+            return Ok(Default::default());
+        };
+
+        // A containerized process writes its map to its own /tmp, named after
+        // its id in its own PID namespace
+        let pid = self.namespace_pids.last().unwrap_or(&self.process_id);
+        let path = format!("/proc/{}/root/tmp/perf-{pid}.map", self.process_id);
+        let reader = match self.process_inspector.read_file(path) {
+            Ok(reader) => reader,
+            Err(e) if e.is_not_found() => return Ok(Default::default()),
+            Err(e) => return Err(SectionPerfMapError::ReadFileFailed(e)),
+        };
+
+        let jit_mappings: Vec<_> = self
+            .mappings
+            .iter()
+            .filter(|m| {
+                m.is_executable()
+                    && m.name
+                        .as_deref()
+                        .is_none_or(|n| n.as_encoded_bytes().starts_with(b"[anon:"))
+            })
+            .map(|m| {
+                let range = &m.system_mapping_info;
+                range.start_address as u64..range.end_address as u64
+            })
+            .collect();
+        let ips = match options.ip_window {
+            Some(_) => self.thread_instruction_pointers(),
+            None => Vec::new(),
+        };
+
+        let mut entries = String::new();
+        for line in BufReader::new(reader).lines() {
+            let line = line.map_err(SectionPerfMapError::IoError)?;
+            let Some((start, size)) = parse_perf_map_line(&line) else {
+                continue;
+            };
+            let end = start.saturating_add(size);
+
+            if !jit_mappings.iter().any(|m| m.contains(&start)) {
+                continue;
+            }
+            if let Some(window) = options.ip_window
+                && !ips.iter().any(|&ip| {
+                    start.saturating_sub(window) <= ip && ip < end.saturating_add(window)
+                })
+            {
+                continue;
+            }
+
+            if entries.len() + line.len() + 1 > options.max_size {
+                soft_errors.push(SectionPerfMapError::Truncated(options.max_size));
+                break;
+            }
+            entries.push_str(&line);
+            entries.push('\n');
+        }
+
+        if entries.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let section = MemoryArrayWriter::write_bytes(buffer, entries.as_bytes());
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxPerfMap as u32,
+            location: section.location(),
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use super::*;
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionSigInfoError {
+    #[error("Failed to convert siginfo to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+/// All of `signalfd_siginfo`. Which fields are meaningful depends on the
+/// signal and `ssi_code`, see `sigaction(2)`.
+#[derive(serde::Serialize)]
+struct SigInfo {
+    ssi_signo: u32,
+    ssi_errno: i32,
+    ssi_code: i32,
+    /// Whether the signal was sent by a process (`kill`, `tgkill`,
+    /// `sigqueue`...) rather than by the kernel, in which case `ssi_pid` and
+    /// `ssi_uid` identify the sender
+    sent_by_process: bool,
+    ssi_pid: u32,
+    ssi_uid: u32,
+    ssi_fd: i32,
+    ssi_tid: u32,
+    ssi_band: u32,
+    ssi_overrun: u32,
+    ssi_trapno: u32,
+    ssi_status: i32,
+    ssi_int: i32,
+    ssi_ptr: u64,
+    ssi_utime: u64,
+    ssi_stime: u64,
+    ssi_addr: u64,
+    ssi_addr_lsb: u16,
+    ssi_syscall: i32,
+    ssi_call_addr: u64,
+    ssi_arch: u32,
+}
+
+impl From<&libc::signalfd_siginfo> for SigInfo {
+    fn from(si: &libc::signalfd_siginfo) -> Self {
+        Self {
+            ssi_signo: si.ssi_signo,
+            ssi_errno: si.ssi_errno,
+            ssi_code: si.ssi_code,
+            // SI_USER, SI_QUEUE, SI_TKILL etc. are all <= 0
+            sent_by_process: si.ssi_code <= 0,
+            ssi_pid: si.ssi_pid,
+            ssi_uid: si.ssi_uid,
+            ssi_fd: si.ssi_fd,
+            ssi_tid: si.ssi_tid,
+            ssi_band: si.ssi_band,
+            ssi_overrun: si.ssi_overrun,
+            ssi_trapno: si.ssi_trapno,
+            ssi_status: si.ssi_status,
+            ssi_int: si.ssi_int,
+            ssi_ptr: si.ssi_ptr,
+            ssi_utime: si.ssi_utime,
+            ssi_stime: si.ssi_stime,
+            ssi_addr: si.ssi_addr,
+            ssi_addr_lsb: si.ssi_addr_lsb,
+            ssi_syscall: si.ssi_syscall,
+            ssi_call_addr: si.ssi_call_addr,
+            ssi_arch: si.ssi_arch,
+        }
+    }
+}
+
+impl MinidumpWriter {
+    /// Writes the complete siginfo of the crash context, if there is one.
+    pub fn write_siginfo_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+    ) -> Result<MDRawDirectory, SectionSigInfoError> {
+        let Some(crash_context) = &self.crash_context else {
+            return Ok(Default::default());
+        };
+
+        let siginfo = SigInfo::from(&crash_context.inner.siginfo);
+        let location =
+            write_json(buffer, &siginfo).map_err(SectionSigInfoError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxSigInfo as u32,
+            location,
+        })
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+
+/// The largest part of the alternate signal stack that is copied into the
+/// minidump. The handler's frames are at the top of the stack, so that is the
+/// part that is kept if the stack is larger.
+const MAX_SIGALTSTACK_LEN: usize = 1024 * 1024;
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionSignalContextError {
+    #[error("Failed to copy the alternate signal stack from the process")]
+    CopyFromProcessError(#[source] CopyFromProcessError),
+    #[error("Failed to sanitize the alternate signal stack")]
+    SanitizeStackCopyFailed(#[source] Box<WriterError>),
+    #[error("Failed to convert signal context to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+#[derive(serde::Serialize)]
+struct SignalContext {
+    uc_flags: u64,
+    /// Signals blocked when the signal was delivered, bit `n - 1` is signal `n`
+    uc_sigmask: u64,
+    sigaltstack: SigAltStack,
+    /// Where the blamed thread currently is, which is normally inside the
+    /// signal handler
+    handler_stack_pointer: Option<u64>,
+    handler_instruction_pointer: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+struct SigAltStack {
+    ss_sp: u64,
+    ss_flags: i32,
+    ss_size: u64,
+    /// Whether the (top of the) stack was added to the memory list
+    captured: bool,
+}
+
+impl MinidumpWriter {
+    /// Writes the signal state found in the crash context, and adds the
+    /// alternate signal stack the handler ran on to the memory list.
+    ///
+    /// This must be called before the memory list stream is written.
+    pub fn write_signal_context_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionSignalContextError>,
+    ) -> Result<MDRawDirectory, SectionSignalContextError> {
+        let Some(crash_context) = &self.crash_context else {
+            return Ok(Default::default());
+        };
+        let context = &crash_context.inner.context;
+        let ss_sp = context.uc_stack.ss_sp as usize;
+        let ss_flags = context.uc_stack.ss_flags;
+        let ss_size = context.uc_stack.ss_size;
+
+        // `uc_flags` is only 32 bits wide on 32-bit architectures
+        #[allow(clippy::useless_conversion)]
+        let uc_flags = u64::from(context.uc_flags);
+        let uc_sigmask = context.uc_sigmask.mask();
+
+        // The blamed thread is stopped in the handler, so its live registers
+        // point into the handler's frames rather than the crash site.
+        let handler_regs = self
+            .threads
+            .iter()
+            .any(|t| t.tid == self.blamed_thread)
+            .then(|| self.thread_info(self.blamed_thread).ok())
+            .flatten()
+            .map(|info| (info.stack_pointer, info.get_instruction_pointer()));
+
+        let mut captured = false;
+        if ss_flags & libc::SS_DISABLE == 0 && ss_size > 0 {
+            let end = ss_sp.saturating_add(ss_size);
+            let start = std::cmp::max(ss_sp, end.saturating_sub(MAX_SIGALTSTACK_LEN));
+
+            // If the crash happened while already on the alternate stack, the
+            // thread stack written for the crashing thread already covers it.
+            let already_captured = self.memory_blocks.iter().any(|block| {
+                let block_start = block.start_of_memory_range as usize;
+                let block_end = block_start + block.memory.data_size as usize;
+                block_start < end && start < block_end
+            });
+
+            if !already_captured {
+                match self.copy_sigaltstack(start, end, handler_regs.map(|(sp, _)| sp)) {
+                    Ok(stack_copy) => {
+                        let section = MemoryArrayWriter::write_bytes(buffer, &stack_copy);
+                        self.memory_blocks.push(MDMemoryDescriptor {
+                            start_of_memory_range: start as u64,
+                            memory: section.location(),
+                        });
+                        captured = true;
+                    }
+                    Err(e) => soft_errors.push(e),
+                }
+            }
+        }
+
+        let signal_context = SignalContext {
+            uc_flags,
+            uc_sigmask,
+            sigaltstack: SigAltStack {
+                ss_sp: ss_sp as u64,
+                ss_flags,
+                ss_size: ss_size as u64,
+                captured,
+            },
+            handler_stack_pointer: handler_regs.map(|(sp, _)| sp as u64),
+            handler_instruction_pointer: handler_regs.map(|(_, ip)| ip as u64),
+        };
+
+        let location = write_json(buffer, &signal_context)
+            .map_err(SectionSignalContextError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxSignalContext as u32,
+            location,
+        })
+    }
+
+    fn copy_sigaltstack(
+        &self,
+        start: usize,
+        end: usize,
+        handler_stack_pointer: Option<usize>,
+    ) -> Result<Vec<u8>, SectionSignalContextError> {
+        let mut stack_copy =
+            MinidumpWriter::copy_from_process(&self.process_inspector, start, end - start)
+                .map_err(SectionSignalContextError::CopyFromProcessError)?;
+
+        if self.sanitize_stack {
+            let sp_offset = handler_stack_pointer
+                .filter(|sp| (start..end).contains(sp))
+                .map_or(0, |sp| sp - start);
+            self.sanitize_stack_copy(&mut stack_copy, start, sp_offset)
+                .map_err(|e| SectionSignalContextError::SanitizeStackCopyFailed(Box::new(e)))?;
+        }
+
+        Ok(stack_copy)
+    }
+}
@@ -0,0 +1,213 @@
+use {
+    super::*,
+    module_reader::DynamicSymbol,
+    std::collections::{HashMap, hash_map::Entry},
+};
+
+/// The most bytes of the crashing thread's stack, from the stack pointer up,
+/// that are scanned for return addresses
+const MAX_STACK_SCAN_SIZE: usize = 64 * 1024;
+
+/// The most hints that are written
+const MAX_SYMBOL_HINTS: usize = 1024;
+
+#[derive(Debug, Error, serde::Serialize)]
+pub enum SectionSymbolHintsError {
+    #[error("Failed to read the dynamic symbols of {module}")]
+    ModuleReaderError {
+        module: String,
+        #[source]
+        error: module_reader::ModuleReaderError,
+    },
+    #[error("Failed to get the stack pointer of the crashing thread")]
+    ThreadInfoError(#[from] ThreadInfoError),
+    #[error("No stack mapping for the stack pointer of the crashing thread")]
+    NoStackPointerMapping,
+    #[error("Failed to copy the stack of the crashing thread")]
+    CopyFromProcessError(#[from] CopyFromProcessError),
+    #[error("Found more than {MAX_SYMBOL_HINTS} addresses, the rest were skipped")]
+    TooManyHints,
+    #[error("Failed to convert the symbol hints to JSON")]
+    ConvertToJsonFailed(
+        #[source]
+        #[serde(skip)]
+        serde_json::Error,
+    ),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum SymbolHintSource {
+    /// The instruction pointer of a thread
+    InstructionPointer,
+    /// A value on the crashing thread's stack that points into code
+    Stack,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct SymbolHint {
+    address: u64,
+    source: SymbolHintSource,
+    module: String,
+    symbol: String,
+    offset: u64,
+}
+
+/// The function symbols of a module, sorted by address, or `None` if they
+/// couldn't be read
+type ModuleFunctions = Option<Vec<DynamicSymbol>>;
+
+impl MinidumpWriter {
+    /// Writes the nearest preceding exported function of each thread's
+    /// instruction pointer and of each value on the crashing thread's stack
+    /// that points into the code of a module, so that frames in modules
+    /// without symbol files can still be named after a dynamic symbol.
+    ///
+    /// This does nothing unless enabled with
+    /// [`MinidumpWriterConfig::capture_symbol_hints`].
+    pub fn write_symbol_hints_stream(
+        &mut self,
+        buffer: &mut DumpBuf,
+        mut soft_errors: impl WriteErrorList<SectionSymbolHintsError>,
+    ) -> Result<MDRawDirectory, SectionSymbolHintsError> {
+        if !self.capture_symbol_hints {
+            return Ok(Default::default());
+        }
+
+        let mut addresses: Vec<(u64, SymbolHintSource)> = self
+            .thread_instruction_pointers()
+            .into_iter()
+            .map(|ip| (ip, SymbolHintSource::InstructionPointer))
+            .collect();
+        match self.crashing_thread_stack_values() {
+            Ok(values) => addresses.extend(
+                values
+                    .into_iter()
+                    .map(|value| (value, SymbolHintSource::Stack)),
+            ),
+            Err(e) => soft_errors.push(e),
+        }
+
+        let mut modules: HashMap<usize, ModuleFunctions> = HashMap::new();
+        let mut hints: Vec<SymbolHint> = Vec::new();
+        for (address, source) in addresses {
+            if self.budget.exceeded() {
+                break;
+            }
+            if hints.iter().any(|hint| hint.address == address) {
+                continue;
+            }
+            let Some(hint) = self.symbol_hint(address, source, &mut modules, &mut soft_errors)
+            else {
+                continue;
+            };
+            if hints.len() == MAX_SYMBOL_HINTS {
+                soft_errors.push(SectionSymbolHintsError::TooManyHints);
+                break;
+            }
+            hints.push(hint);
+        }
+
+        if hints.is_empty() {
+            return Ok(Default::default());
+        }
+
+        let location =
+            write_json(buffer, &hints).map_err(SectionSymbolHintsError::ConvertToJsonFailed)?;
+        Ok(MDRawDirectory {
+            stream_type: MDExtStreamType::LinuxSymbolHints as u32,
+            location,
+        })
+    }
+
+    /// The pointer-sized values on the crashing thread's stack that lie in
+    /// the code of a module.
+    fn crashing_thread_stack_values(&self) -> Result<Vec<u64>, SectionSymbolHintsError> {
+        let stack_pointer = match &self.crash_context {
+            Some(crash_context) => crash_context.get_stack_pointer(),
+            None => self.thread_info(self.blamed_thread)?.stack_pointer,
+        };
+        let (stack, stack_len) = self
+            .get_stack_info(stack_pointer)
+            .map_err(|_| SectionSymbolHintsError::NoStackPointerMapping)?;
+        let start = stack_pointer.clamp(stack, stack + stack_len);
+        let len = (stack + stack_len - start).min(MAX_STACK_SCAN_SIZE);
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let bytes = Self::copy_from_process(&self.process_inspector, start, len)?;
+
+        Ok(bytes
+            .chunks_exact(std::mem::size_of::<usize>())
+            .map(|chunk| usize::from_ne_bytes(chunk.try_into().unwrap()))
+            .filter(|&value| {
+                self.find_mapping(value)
+                    .is_some_and(|m| m.is_interesting() && m.is_executable())
+            })
+            .map(|value| value as u64)
+            .collect())
+    }
+
+    fn symbol_hint(
+        &self,
+        address: u64,
+        source: SymbolHintSource,
+        modules: &mut HashMap<usize, ModuleFunctions>,
+        soft_errors: &mut impl WriteErrorList<SectionSymbolHintsError>,
+    ) -> Option<SymbolHint> {
+        let (idx, mapping) = self.mappings.iter().enumerate().find(|(_, m)| {
+            m.is_interesting() && m.is_executable() && m.contains_address(address as usize)
+        })?;
+        let module = mapping.name.as_deref()?.to_string_lossy().into_owned();
+
+        let functions = match modules.entry(idx) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let functions = match self.module_functions(mapping) {
+                    Ok(functions) => Some(functions),
+                    Err(error) => {
+                        soft_errors.push(SectionSymbolHintsError::ModuleReaderError {
+                            module: module.clone(),
+                            error,
+                        });
+                        None
+                    }
+                };
+                entry.insert(functions)
+            }
+        };
+        let functions = functions.as_ref()?;
+
+        let relative = address.checked_sub(mapping.start_address as u64)?;
+        let nearest = functions.partition_point(|sym| sym.address <= relative);
+        let symbol = &functions[nearest.checked_sub(1)?];
+        Some(SymbolHint {
+            address,
+            source,
+            module,
+            symbol: symbol.name.clone(),
+            offset: relative - symbol.address,
+        })
+    }
+
+    fn module_functions(
+        &self,
+        mapping: &MappingInfo,
+    ) -> Result<Vec<DynamicSymbol>, module_reader::ModuleReaderError> {
+        let reader = self.process_inspector.process_reader();
+        let module_memory =
+            module_reader::ProcessModuleMemoryReader::new(&reader, mapping.start_address);
+
+        let mut functions: Vec<_> = module_reader::read_dynamic_symbols_from_module(module_memory)?
+            .into_iter()
+            .filter(|sym| {
+                matches!(
+                    sym.symbol_type,
+                    goblin::elf::sym::STT_FUNC | goblin::elf::sym::STT_GNU_IFUNC
+                )
+            })
+            .collect();
+        functions.sort_by_key(|sym| sym.address);
+        Ok(functions)
+    }
+}
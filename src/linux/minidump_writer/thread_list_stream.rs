@@ -20,6 +20,8 @@ const LIMIT_MINIDUMP_FUDGE_FACTOR: u64 = 64 * 1024;
 enum MaxStackLen {
     None,
     Len(usize),
+    /// The stream is out of time, so the stack isn't dumped at all
+    Skip,
 }
 
 #[derive(Debug, Error, serde::Serialize)]
@@ -159,12 +161,13 @@ impl MinidumpWriter {
                     CrashingThreadContext::CrashContext(cpu_section.location());
             } else {
                 let info = self.get_thread_info_by_index(idx)?;
-                let max_stack_len =
-                    if self.minidump_size_limit.is_some() && idx >= LIMIT_BASE_THREAD_COUNT {
-                        extra_thread_stack_len
-                    } else {
-                        MaxStackLen::None // default to no maximum for this thread
-                    };
+                let max_stack_len = if item.tid != self.blamed_thread && self.budget.exceeded() {
+                    MaxStackLen::Skip
+                } else if self.minidump_size_limit.is_some() && idx >= LIMIT_BASE_THREAD_COUNT {
+                    extra_thread_stack_len
+                } else {
+                    MaxStackLen::None // default to no maximum for this thread
+                };
                 let instruction_ptr = info.get_instruction_pointer();
                 self.fill_thread_stack(
                     buffer,
@@ -203,7 +206,11 @@ impl MinidumpWriter {
         thread.stack.start_of_memory_range = stack_ptr.try_into()?;
         thread.stack.memory.data_size = 0;
         thread.stack.memory.rva = buffer.position() as u32;
+        self.stack_pointers.push(stack_ptr);
 
+        if let MaxStackLen::Skip = max_stack_len {
+            return Ok(());
+        }
         if let Ok((valid_stack_ptr, stack_len)) = self.get_stack_info(stack_ptr) {
             let stack_len = if let MaxStackLen::Len(max_stack_len) = max_stack_len {
                 min(stack_len, max_stack_len)
@@ -0,0 +1,189 @@
+//! A sentinel process that continues the dumped process if the dumper dies
+//! while it is stopped, as neither `Drop` nor any other cleanup runs then.
+
+use {
+    crate::Pid,
+    std::{
+        io::Error,
+        os::fd::{AsRawFd, FromRawFd, OwnedFd},
+        time::Duration,
+    },
+};
+
+/// A forked process that waits for the dumper to either disarm it or die.
+///
+/// If the dumper dies first, the kernel detaches its threads from the process
+/// but leaves the process stopped, so the sentinel sends it `SIGCONT`. If the
+/// deadline passes first, the sentinel kills the dumper, as only the tracing
+/// threads can resume the threads they are attached to, and then continues the
+/// process.
+#[derive(Debug)]
+pub struct Watchdog {
+    sentinel: Pid,
+    /// The write end of the pipe the sentinel waits on
+    pipe: OwnedFd,
+}
+
+impl Watchdog {
+    /// Forks the sentinel for the process `pid`
+    pub fn spawn(pid: Pid, deadline: Duration) -> Result<Self, Error> {
+        let dumper = std::process::id() as Pid;
+
+        // Becomes readable once the dumper has exited and its tracees are
+        // detached. Before Linux 5.3 the sentinel relies on the pipe alone,
+        // which is closed slightly earlier.
+        let dumper_fd = pidfd_open(dumper);
+        // Signals are sent through pidfds where possible, so that they can't
+        // reach another process that reused the pid of one that is gone
+        let pid_fd = pidfd_open(pid);
+
+        let mut fds = [-1; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+            return Err(Error::last_os_error());
+        }
+        let (read_end, write_end) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+
+        let mut end = now();
+        end.tv_sec += deadline.as_secs() as libc::time_t;
+        end.tv_nsec += deadline.subsec_nanos() as libc::c_long;
+        if end.tv_nsec >= 1_000_000_000 {
+            end.tv_sec += 1;
+            end.tv_nsec -= 1_000_000_000;
+        }
+
+        match unsafe { libc::fork() } {
+            -1 => Err(Error::last_os_error()),
+            0 => {
+                unsafe { libc::close(write_end.as_raw_fd()) };
+                sentinel(
+                    read_end.as_raw_fd(),
+                    dumper_fd.as_ref().map_or(-1, |fd| fd.as_raw_fd()),
+                    dumper,
+                    pid_fd.as_ref().map_or(-1, |fd| fd.as_raw_fd()),
+                    pid,
+                    end,
+                )
+            }
+            sentinel => Ok(Self {
+                sentinel,
+                pipe: write_end,
+            }),
+        }
+    }
+
+    /// Tells the sentinel to exit without touching the process, and reaps it
+    pub fn disarm(self) {
+        let _ = unsafe { libc::write(self.pipe.as_raw_fd(), [0u8].as_ptr().cast(), 1) };
+        drop(self.pipe);
+        let _ = unsafe { libc::waitpid(self.sentinel, std::ptr::null_mut(), 0) };
+    }
+}
+
+fn pidfd_open(pid: Pid) -> Option<OwnedFd> {
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid, 0) };
+    (fd >= 0).then(|| unsafe { OwnedFd::from_raw_fd(fd as _) })
+}
+
+/// Sends a signal through `pidfd`, or to `pid` if it couldn't be opened
+unsafe fn send_signal(pidfd: libc::c_int, pid: Pid, signal: libc::c_int) {
+    unsafe {
+        if pidfd >= 0 {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                pidfd,
+                signal,
+                std::ptr::null::<libc::siginfo_t>(),
+                0,
+            );
+        } else {
+            libc::kill(pid, signal);
+        }
+    }
+}
+
+fn interrupted() -> bool {
+    Error::last_os_error().raw_os_error() == Some(libc::EINTR)
+}
+
+fn now() -> libc::timespec {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    now
+}
+
+/// The body of the sentinel, which may only make async-signal-safe calls as it
+/// is forked from a possibly multithreaded dumper
+fn sentinel(
+    pipe: libc::c_int,
+    dumper_fd: libc::c_int,
+    dumper: Pid,
+    pid_fd: libc::c_int,
+    pid: Pid,
+    end: libc::timespec,
+) -> ! {
+    unsafe {
+        // Leave the dumper's session, so that e.g. a ^C aimed at it doesn't
+        // kill the sentinel as well
+        libc::setsid();
+
+        let mut fds = [
+            libc::pollfd {
+                fd: pipe,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: dumper_fd,
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        let mut deadline_passed = false;
+        loop {
+            let timeout = if deadline_passed {
+                -1
+            } else {
+                let now = now();
+                // time_t and c_long are only 32 bits wide on some targets
+                #[allow(clippy::useless_conversion)]
+                let remaining_ms = i64::from(end.tv_sec - now.tv_sec) * 1000
+                    + i64::from(end.tv_nsec - now.tv_nsec) / 1_000_000;
+                if remaining_ms <= 0 {
+                    deadline_passed = true;
+                    send_signal(dumper_fd, dumper, libc::SIGKILL);
+                    continue;
+                }
+                remaining_ms.min(libc::c_int::MAX as i64) as libc::c_int
+            };
+
+            match libc::poll(fds.as_mut_ptr(), fds.len() as _, timeout) {
+                0 => continue,
+                -1 if interrupted() => continue,
+                -1 => break,
+                _ => {}
+            }
+
+            if fds[1].revents != 0 {
+                break;
+            }
+            if fds[0].revents != 0 {
+                let mut byte = 0u8;
+                match libc::read(pipe, (&mut byte as *mut u8).cast(), 1) {
+                    // Disarmed
+                    1 => libc::_exit(0),
+                    -1 if interrupted() => {}
+                    // The dumper is exiting, wait for it to be gone if possible
+                    _ if dumper_fd >= 0 => fds[0].fd = -1,
+                    _ => break,
+                }
+            }
+        }
+
+        send_signal(pid_fd, pid, libc::SIGCONT);
+        libc::_exit(0)
+    }
+}
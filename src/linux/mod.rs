@@ -3,14 +3,16 @@
 
 pub use {
     maps_reader::LINUX_GATE_LIBRARY_NAME,
-    process_inspection::{Error as BackendError, process_reader},
+    process_inspection::{Error as BackendError, PreopenedDirs, process_reader},
 };
 
+pub mod annotations;
 pub mod app_memory;
 mod crash_context_ext;
 pub mod maps_reader;
 pub mod minidump_writer;
 pub mod module_reader;
+pub mod probe;
 pub mod thread_info;
 
 pub(crate) mod auxv;
@@ -1,6 +1,7 @@
 use {
     super::{
-        process_inspection::{self, ProcessInspector},
+        maps_reader::MappingInfo,
+        process_inspection::{self, MappedModuleMemoryReader, ProcessInspector},
         serializers::*,
     },
     crate::module_reader::{ModuleMemoryReadError, ProcessModuleMemoryReader},
@@ -11,14 +12,24 @@ use {
     },
     std::{
         borrow::Cow,
+        collections::HashMap,
         ffi::{CStr, OsString},
-        path::Path,
+        path::{Path, PathBuf},
     },
 };
 
 type Error = ModuleReaderError;
 
 const NOTE_SECTION_NAME: &[u8] = b".note.gnu.build-id\0";
+const DEBUGLINK_SECTION_NAME: &[u8] = b".gnu_debuglink\0";
+const DEBUGDATA_SECTION_NAME: &[u8] = b".gnu_debugdata\0";
+
+/// Upper bound on the size of a `.gnu_debugdata` section that is read.
+pub const MAX_DEBUG_DATA_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Upper bound on the number of dynamic symbols read from a module, so that
+/// corrupt hash tables don't lead to huge reads.
+const MAX_DYNAMIC_SYMBOLS: u64 = 1 << 20;
 
 #[derive(Debug, thiserror::Error, serde::Serialize)]
 pub enum ModuleReaderError {
@@ -70,6 +81,33 @@ pub enum ModuleReaderError {
     NoSoNameEntry,
     #[error("no dynamic linking information section")]
     NoDynamicSection,
+    #[error("no dynamic symbol table")]
+    NoDynSymSection,
+    #[error("no hash table from which to determine the number of dynamic symbols")]
+    NoSymbolHashTable,
+    #[error("the dynamic symbol table has too many entries ({0})")]
+    TooManyDynamicSymbols(u64),
+    #[error("the ELF data has no loadable segments or allocated sections")]
+    NoLoadableSegments,
+    #[error("the address {0:#x} is not within any loadable segment")]
+    AddressNotLoaded(u64),
+    #[error("no .gnu_debuglink section")]
+    NoDebugLinkSection,
+    #[error("the .gnu_debuglink section is malformed")]
+    InvalidDebugLink,
+    #[error("no .gnu_debugdata section")]
+    NoDebugDataSection,
+    #[error("the .gnu_debugdata section is too large ({0} bytes)")]
+    DebugDataTooLarge(u64),
+    #[error(
+        "failed to read dynamic symbols\n\
+    ... from program headers: {program_headers}\n\
+    ... from sections: {section}"
+    )]
+    NoDynamicSymbols {
+        program_headers: Box<Self>,
+        section: Box<Self>,
+    },
     #[error(
         "failed to retrieve soname\n\
     ... from program headers: {program_headers}\n\
@@ -79,6 +117,8 @@ pub enum ModuleReaderError {
         program_headers: Box<Self>,
         section: Box<Self>,
     },
+    #[error("the mapping has no file")]
+    NoModuleFile,
     #[error("Not safe to open mapping {}", .0.to_string_lossy())]
     NotSafeToOpenMapping(OsString),
     #[error("Mmapped file empty or not an ELF file")]
@@ -114,7 +154,7 @@ fn is_executable_section(header: &elf::SectionHeader) -> bool {
 ///
 /// This provides `size_of::<GUID>` bytes to keep identifiers produced by this function compatible
 /// with other build ids.
-fn build_id_from_bytes(data: &[u8]) -> Vec<u8> {
+pub fn build_id_from_bytes(data: &[u8]) -> Vec<u8> {
     // Only provide mem::size_of(MDGUID) bytes to keep identifiers produced by this
     // function backwards-compatible.
     data.chunks(std::mem::size_of::<GUID>()).fold(
@@ -147,7 +187,7 @@ fn section_header_with_name<'sc, MM: ReadModuleMemory>(
             log::warn!("invalid sh_name offset for {name:?}");
             continue;
         }
-        if sh_name + name.len() as u64 >= strtab_section_header.sh_size {
+        if sh_name + name.len() as u64 > strtab_section_header.sh_size {
             // This can't be a match.
             continue;
         }
@@ -159,13 +199,123 @@ fn section_header_with_name<'sc, MM: ReadModuleMemory>(
     Ok(None)
 }
 
+/// Where the file of a module was opened from, see [`ModuleFiles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleFileSource {
+    /// `/proc/<pid>/map_files/<range>`, the file that is actually mapped, even
+    /// if it was deleted or is in another mount namespace
+    MapFiles,
+    /// `/proc/<pid>/root/<path>`, the path in the process's mount namespace
+    ProcessRoot,
+    /// The path in the dumper's own mount namespace
+    Path,
+}
+
+/// Locates the files that the modules of a process were mapped from.
+///
+/// Mapping names are paths in the mount namespace of the process, and the
+/// file may have been deleted or replaced since it was mapped, so the
+/// `/proc/<pid>/map_files` entry of a mapping is preferred, then the path
+/// under `/proc/<pid>/root`, and only then the path itself.
+#[derive(Debug, Default)]
+pub struct ModuleFiles {
+    map_files_dir: PathBuf,
+    root: PathBuf,
+    /// The `map_files` entries, which are named after the address range of
+    /// each file mapping, by start address
+    map_files: HashMap<usize, OsString>,
+}
+
+impl ModuleFiles {
+    pub fn new(process_inspector: &ProcessInspector, pid: libc::pid_t) -> Self {
+        let map_files_dir = PathBuf::from(format!("/proc/{pid}/map_files"));
+        let map_files = match process_inspector.read_dir(&map_files_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| {
+                    let entry = entry.ok()?;
+                    let (start, _) = entry.to_str()?.split_once('-')?;
+                    let start = usize::from_str_radix(start, 16).ok()?;
+                    Some((start, entry))
+                })
+                .collect(),
+            Err(e) => {
+                log::warn!("failed to list {}: {e}", map_files_dir.display());
+                HashMap::new()
+            }
+        };
+
+        Self {
+            map_files_dir,
+            root: PathBuf::from(format!("/proc/{pid}/root")),
+            map_files,
+        }
+    }
+
+    /// The paths the file of the mapping may be found at, in order of
+    /// preference.
+    pub fn candidates(&self, mapping: &MappingInfo) -> Vec<(PathBuf, ModuleFileSource)> {
+        let map_files = self
+            .map_files_path(mapping)
+            .map(|path| (path, ModuleFileSource::MapFiles));
+        let root = self
+            .root_path(mapping)
+            .map(|path| (path, ModuleFileSource::ProcessRoot));
+        let path = mapping
+            .name
+            .as_ref()
+            .map(|name| (PathBuf::from(name), ModuleFileSource::Path));
+        map_files.into_iter().chain(root).chain(path).collect()
+    }
+
+    /// Maps the file of the mapping into memory from the first candidate path
+    /// that can be opened.
+    pub fn map_module_into_memory(
+        &self,
+        process_inspector: &ProcessInspector,
+        mapping: &MappingInfo,
+        offset: u64,
+    ) -> Result<(MappedModuleMemoryReader, ModuleFileSource), Error> {
+        let mut error = Error::NoModuleFile;
+        for (path, source) in self.candidates(mapping) {
+            match process_inspector.map_module_into_memory(&path, offset) {
+                Ok(reader) => {
+                    log::debug!("mapped module file from {}", path.display());
+                    return Ok((reader, source));
+                }
+                Err(e) => {
+                    log::debug!("failed to map module file from {}: {e}", path.display());
+                    error = Error::MapModuleFailed(e);
+                }
+            }
+        }
+        Err(error)
+    }
+
+    /// The `map_files` entry of the mapping, if there is one.
+    pub fn map_files_path(&self, mapping: &MappingInfo) -> Option<PathBuf> {
+        self.map_files
+            .get(&mapping.system_mapping_info.start_address)
+            .map(|entry| self.map_files_dir.join(entry))
+    }
+
+    /// The path of the mapping in the process's mount namespace.
+    pub fn root_path(&self, mapping: &MappingInfo) -> Option<PathBuf> {
+        if self.root.as_os_str().is_empty() {
+            return None;
+        }
+        let relative = Path::new(mapping.name.as_deref()?).strip_prefix("/").ok()?;
+        Some(self.root.join(relative))
+    }
+}
+
 pub fn read_build_id_from_file(
     process_inspector: &ProcessInspector,
-    path: &Path,
+    module_files: &ModuleFiles,
+    mapping: &MappingInfo,
 ) -> Result<Vec<u8>, Error> {
-    let module_memory_reader = process_inspector
-        .map_module_into_memory(path, 0)
-        .map_err(Error::MapModuleFailed)?;
+    let (module_memory_reader, _) =
+        module_files.map_module_into_memory(process_inspector, mapping, 0)?;
     read_build_id_from_module(module_memory_reader)
 }
 
@@ -192,22 +342,22 @@ pub fn read_build_id_from_module(module_memory: impl ReadModuleMemory) -> Result
 
 pub fn read_soname_from_file(
     process_inspector: &ProcessInspector,
-    path: &Path,
-    offset: usize,
+    module_files: &ModuleFiles,
+    mapping: &MappingInfo,
 ) -> Result<String, Error> {
-    let offset = u64::try_from(offset).map_err(Error::LinuxGateNotConvertable)?;
+    let offset = u64::try_from(mapping.offset).map_err(Error::LinuxGateNotConvertable)?;
 
     // It is unsafe to attempt to open a mapped file that lives under /dev,
     // because the semantics of the open may be driver-specific so we'd risk
     // hanging the crash dumper. And a file in /dev/ almost certainly has no
     // ELF file identifier anyways.
+    let path = Path::new(mapping.name.as_deref().unwrap_or_default());
     if path.starts_with("/dev/") {
         return Err(Error::NotSafeToOpenMapping(path.as_os_str().to_os_string()));
     }
 
-    let module_memory_reader = process_inspector
-        .map_module_into_memory(path, offset)
-        .map_err(Error::MapModuleFailed)?;
+    let (module_memory_reader, _) =
+        module_files.map_module_into_memory(process_inspector, mapping, offset)?;
 
     let memory_len = module_memory_reader.len().map_err(Error::MapModuleFailed)?;
 
@@ -234,6 +384,58 @@ pub fn read_soname_from_module(module_memory: impl ReadModuleMemory) -> Result<S
     })
 }
 
+/// Read the defined symbols of the dynamic symbol table of a module.
+pub fn read_dynamic_symbols_from_module(
+    module_memory: impl ReadModuleMemory,
+) -> Result<Vec<DynamicSymbol>, Error> {
+    let reader = ModuleReader::new(module_memory)?;
+    let program_headers = match reader.dynamic_symbols_from_program_headers() {
+        Ok(v) => return Ok(v),
+        Err(e) => Box::new(e),
+    };
+    let section = match reader.dynamic_symbols_from_sections() {
+        Ok(v) => return Ok(v),
+        Err(e) => Box::new(e),
+    };
+    Err(Error::NoDynamicSymbols {
+        program_headers,
+        section,
+    })
+}
+
+/// Find a defined symbol with the given name in the dynamic symbol table of a
+/// module.
+pub fn find_dynamic_symbol_in_module(
+    module_memory: impl ReadModuleMemory,
+    name: &str,
+) -> Result<Option<DynamicSymbol>, Error> {
+    Ok(read_dynamic_symbols_from_module(module_memory)?
+        .into_iter()
+        .find(|sym| sym.name == name))
+}
+
+/// The contents of a `.gnu_debuglink` section, naming the separate file that
+/// holds the debug information of a module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DebugLink {
+    pub file_name: String,
+    /// The CRC-32 of the debug file
+    pub crc: u32,
+}
+
+/// A defined symbol from the dynamic symbol table of a module.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DynamicSymbol {
+    pub name: String,
+    /// The address of the symbol relative to the start of the module, use
+    /// [`ReadModuleMemory::relative_to_absolute`] to get its address in the
+    /// process.
+    pub address: u64,
+    pub size: u64,
+    /// The `STT_*` type of the symbol
+    pub symbol_type: u8,
+}
+
 struct DynIter<'a> {
     data: &'a [u8],
     offset: usize,
@@ -413,6 +615,151 @@ impl<MM: ReadModuleMemory> ModuleReader<MM> {
         Err(Error::NoSoNameEntry)
     }
 
+    /// Read the dynamic symbols using program headers to locate dynamic linking
+    /// information.
+    ///
+    /// The number of symbols is taken from the `DT_HASH` or `DT_GNU_HASH` hash
+    /// table, as the dynamic section doesn't record it.
+    pub fn dynamic_symbols_from_program_headers(&self) -> Result<Vec<DynamicSymbol>, Error> {
+        let program_headers = self.read_program_headers()?;
+        let vaddr_base = Self::vaddr_base(&program_headers);
+
+        let dynamic_segment_header = program_headers
+            .iter()
+            .find(|h| h.p_type == elf::program_header::PT_DYNAMIC)
+            .ok_or(Error::NoDynamicSection)?;
+        let dynamic_section = self.module_memory.read(
+            self.vaddr_to_offset(&program_headers, dynamic_segment_header.p_vaddr)?,
+            if self.module_memory.is_process_memory() {
+                dynamic_segment_header.p_memsz
+            } else {
+                dynamic_segment_header.p_filesz
+            },
+        )?;
+
+        let mut symtab_addr = None;
+        let mut strtab_addr = None;
+        let mut strtab_size = None;
+        let mut hash_addr = None;
+        let mut gnu_hash_addr = None;
+        for dyn_ in DynIter::new(&dynamic_section, self.context) {
+            let dyn_ = dyn_?;
+            match dyn_.d_tag {
+                elf::dynamic::DT_SYMTAB => symtab_addr = Some(dyn_.d_val),
+                elf::dynamic::DT_STRTAB => strtab_addr = Some(dyn_.d_val),
+                elf::dynamic::DT_STRSZ => strtab_size = Some(dyn_.d_val),
+                elf::dynamic::DT_HASH => hash_addr = Some(dyn_.d_val),
+                elf::dynamic::DT_GNU_HASH => gnu_hash_addr = Some(dyn_.d_val),
+                _ => (),
+            }
+        }
+
+        let (Some(strtab_addr), Some(strtab_size)) = (strtab_addr, strtab_size) else {
+            return Err(Error::NoDynStrSection);
+        };
+        let symtab_addr = symtab_addr.ok_or(Error::NoDynSymSection)?;
+
+        // If loaded in memory, these addresses will normally have been relocated
+        // to be absolute.
+        let to_offset = |addr: u64| match self.module_memory.absolute_to_relative(addr) {
+            Some(offset) if self.module_memory.is_process_memory() => Ok(offset),
+            _ => self.vaddr_to_offset(&program_headers, addr),
+        };
+
+        let count = if let Some(addr) = gnu_hash_addr {
+            self.gnu_hash_symbol_count(to_offset(addr)?)?
+        } else if let Some(addr) = hash_addr {
+            // The number of entries in the chain array equals the number of
+            // symbols.
+            self.read_u32(to_offset(addr)? + 4)?.into()
+        } else {
+            return Err(Error::NoSymbolHashTable);
+        };
+
+        self.read_dynamic_symbols(
+            to_offset(symtab_addr)?,
+            count,
+            to_offset(strtab_addr)?,
+            strtab_size,
+            vaddr_base,
+        )
+    }
+
+    /// Read the dynamic symbols using section headers to locate the `.dynsym`
+    /// section.
+    pub fn dynamic_symbols_from_sections(&self) -> Result<Vec<DynamicSymbol>, Error> {
+        let section_headers = self.read_section_headers()?;
+
+        let dynsym_section_header = section_headers
+            .iter()
+            .find(|h| h.sh_type == elf::section_header::SHT_DYNSYM)
+            .ok_or(Error::NoDynSymSection)?;
+        let dynstr_section_header = section_headers
+            .get(dynsym_section_header.sh_link as usize)
+            .filter(|h| h.sh_type == elf::section_header::SHT_STRTAB)
+            .ok_or(Error::NoDynStrSection)?;
+
+        let vaddr_base = self
+            .read_program_headers()
+            .map_or(0, |headers| Self::vaddr_base(&headers));
+        let count = dynsym_section_header
+            .sh_size
+            .checked_div(dynsym_section_header.sh_entsize)
+            .unwrap_or(0);
+
+        self.read_dynamic_symbols(
+            self.section_offset(dynsym_section_header),
+            count,
+            self.section_offset(dynstr_section_header),
+            dynstr_section_header.sh_size,
+            vaddr_base,
+        )
+    }
+
+    /// The range of virtual addresses the module occupies when loaded, as
+    /// described by its `PT_LOAD` segments or, if it has none, by its allocated
+    /// sections.
+    ///
+    /// In-memory objects such as those registered through the GDB JIT interface
+    /// have their section addresses set to where the code actually lives, so
+    /// this gives the range of that code.
+    pub fn loaded_address_range(&self) -> Result<std::ops::Range<u64>, Error> {
+        let range = |ranges: &mut dyn Iterator<Item = (u64, u64)>| {
+            ranges.filter(|&(_, size)| size > 0).fold(
+                None,
+                |acc: Option<std::ops::Range<u64>>, (start, size)| {
+                    let end = start.saturating_add(size);
+                    Some(match acc {
+                        Some(r) => r.start.min(start)..r.end.max(end),
+                        None => start..end,
+                    })
+                },
+            )
+        };
+
+        if let Ok(program_headers) = self.read_program_headers()
+            && let Some(r) = range(
+                &mut program_headers
+                    .iter()
+                    .filter(|h| h.p_type == elf::program_header::PT_LOAD)
+                    .map(|h| (h.p_vaddr, h.p_memsz)),
+            )
+        {
+            return Ok(r);
+        }
+
+        range(
+            &mut self
+                .read_section_headers()?
+                .iter()
+                .filter(|h| {
+                    h.sh_flags & u64::from(elf::section_header::SHF_ALLOC) != 0 && h.sh_addr != 0
+                })
+                .map(|h| (h.sh_addr, h.sh_size)),
+        )
+        .ok_or(Error::NoLoadableSegments)
+    }
+
     /// Read the build id from a program header note.
     pub fn build_id_from_program_headers(&self) -> Result<Vec<u8>, Error> {
         let program_headers = self.read_program_headers()?;
@@ -464,6 +811,54 @@ impl<MM: ReadModuleMemory> ModuleReader<MM> {
         Ok(build_id_from_bytes(&text_data))
     }
 
+    /// Read the name and CRC of the separate debug file from the
+    /// `.gnu_debuglink` section.
+    ///
+    /// The section isn't loaded, so this only works on module files.
+    pub fn debug_link_from_section(&self) -> Result<DebugLink, Error> {
+        let section_headers = self.read_section_headers()?;
+        let header = section_header_with_name(
+            &section_headers,
+            self.header.e_shstrndx as usize,
+            DEBUGLINK_SECTION_NAME,
+            &self.module_memory,
+        )?
+        .ok_or(Error::NoDebugLinkSection)?;
+
+        let data = self.module_memory.read(header.sh_offset, header.sh_size)?;
+        // The nul-terminated file name is padded to a multiple of 4 bytes and
+        // followed by the CRC.
+        let file_name = CStr::from_bytes_until_nul(&data).map_err(|_| Error::InvalidDebugLink)?;
+        let crc_offset = (file_name.to_bytes_with_nul().len() + 3) & !3;
+        let crc = data
+            .get(crc_offset..crc_offset + 4)
+            .ok_or(Error::InvalidDebugLink)?;
+        Ok(DebugLink {
+            file_name: file_name.to_string_lossy().into_owned(),
+            crc: self.u32_from_bytes(crc),
+        })
+    }
+
+    /// Read the `.gnu_debugdata` section ("MiniDebugInfo"), an xz-compressed
+    /// ELF file holding a minimal symbol table.
+    ///
+    /// The section isn't loaded, so this only works on module files.
+    pub fn debug_data_from_section(&self) -> Result<Cow<'_, [u8]>, Error> {
+        let section_headers = self.read_section_headers()?;
+        let header = section_header_with_name(
+            &section_headers,
+            self.header.e_shstrndx as usize,
+            DEBUGDATA_SECTION_NAME,
+            &self.module_memory,
+        )?
+        .ok_or(Error::NoDebugDataSection)?;
+
+        if header.sh_size > MAX_DEBUG_DATA_SIZE {
+            return Err(Error::DebugDataTooLarge(header.sh_size));
+        }
+        Ok(self.module_memory.read(header.sh_offset, header.sh_size)?)
+    }
+
     fn read_segment<'a>(&'a self, header: &elf::ProgramHeader) -> Result<Cow<'a, [u8]>, Error> {
         let (offset, size) = if self.module_memory.is_process_memory() {
             (header.p_vaddr, header.p_memsz)
@@ -474,6 +869,123 @@ impl<MM: ReadModuleMemory> ModuleReader<MM> {
         self.module_memory.read(offset, size).map_err(|e| e.into())
     }
 
+    fn read_dynamic_symbols(
+        &self,
+        symtab_offset: u64,
+        count: u64,
+        strtab_offset: u64,
+        strtab_size: u64,
+        vaddr_base: u64,
+    ) -> Result<Vec<DynamicSymbol>, Error> {
+        if count > MAX_DYNAMIC_SYMBOLS {
+            return Err(Error::TooManyDynamicSymbols(count));
+        }
+        if count == 0 || strtab_size == 0 {
+            return Ok(Vec::new());
+        }
+
+        let sym_size = elf::Sym::size(self.context.container) as u64;
+        let symtab_data = self.module_memory.read(symtab_offset, count * sym_size)?;
+        let symtab = elf::Symtab::parse(&symtab_data, 0, count as usize, self.context)?;
+        let strtab = self.module_memory.read(strtab_offset, strtab_size)?;
+
+        Ok(symtab
+            .iter()
+            .filter(|sym| sym.st_shndx != elf::section_header::SHN_UNDEF as usize)
+            .filter_map(|sym| {
+                let name = CStr::from_bytes_until_nul(strtab.get(sym.st_name..)?).ok()?;
+                (!name.is_empty()).then(|| DynamicSymbol {
+                    name: name.to_string_lossy().into_owned(),
+                    address: sym.st_value.wrapping_sub(vaddr_base),
+                    size: sym.st_size,
+                    symbol_type: sym.st_type(),
+                })
+            })
+            .collect())
+    }
+
+    /// Determine the number of dynamic symbols from a `DT_GNU_HASH` table.
+    ///
+    /// The table doesn't store the count, so this finds the highest symbol index
+    /// that any bucket refers to and follows its hash chain to the end.
+    fn gnu_hash_symbol_count(&self, offset: u64) -> Result<u64, Error> {
+        let nbuckets = u64::from(self.read_u32(offset)?);
+        let symoffset = u64::from(self.read_u32(offset + 4)?);
+        let bloom_size = u64::from(self.read_u32(offset + 8)?);
+        let word_size = match self.context.container {
+            Container::Little => 4,
+            Container::Big => 8,
+        };
+
+        let buckets_offset = offset + 16 + bloom_size * word_size;
+        let buckets = self.module_memory.read(buckets_offset, nbuckets * 4)?;
+        let max_bucket = buckets
+            .chunks_exact(4)
+            .map(|b| u64::from(self.u32_from_bytes(b)))
+            .max()
+            .unwrap_or(0);
+        if max_bucket < symoffset {
+            return Ok(symoffset);
+        }
+
+        let chain_offset = buckets_offset + nbuckets * 4;
+        let mut index = max_bucket;
+        loop {
+            if index - symoffset > MAX_DYNAMIC_SYMBOLS {
+                return Err(Error::TooManyDynamicSymbols(index));
+            }
+            let hash = self.read_u32(chain_offset + (index - symoffset) * 4)?;
+            index += 1;
+            // The lowest bit marks the end of a chain.
+            if hash & 1 != 0 {
+                return Ok(index);
+            }
+        }
+    }
+
+    fn read_u32(&self, offset: u64) -> Result<u32, Error> {
+        let data = self.module_memory.read(offset, 4)?;
+        Ok(self.u32_from_bytes(&data))
+    }
+
+    fn u32_from_bytes(&self, data: &[u8]) -> u32 {
+        let bytes = data[..4].try_into().unwrap();
+        if self.context.is_little_endian() {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        }
+    }
+
+    /// The virtual address the start of the module is linked at, which is 0 for
+    /// position independent modules.
+    fn vaddr_base(program_headers: &[elf::ProgramHeader]) -> u64 {
+        program_headers
+            .iter()
+            .find(|h| h.p_type == elf::program_header::PT_LOAD)
+            .map_or(0, |h| h.p_vaddr.saturating_sub(h.p_offset))
+    }
+
+    /// Convert a virtual address as found in the ELF data to an offset into the
+    /// module memory.
+    fn vaddr_to_offset(
+        &self,
+        program_headers: &[elf::ProgramHeader],
+        vaddr: u64,
+    ) -> Result<u64, Error> {
+        if self.module_memory.is_process_memory() {
+            return vaddr
+                .checked_sub(Self::vaddr_base(program_headers))
+                .ok_or(Error::AddressNotLoaded(vaddr));
+        }
+        program_headers
+            .iter()
+            .filter(|h| h.p_type == elf::program_header::PT_LOAD)
+            .find(|h| h.p_vaddr <= vaddr && vaddr - h.p_vaddr < h.p_filesz)
+            .map(|h| vaddr - h.p_vaddr + h.p_offset)
+            .ok_or(Error::AddressNotLoaded(vaddr))
+    }
+
     fn read_name_from_strtab(
         &self,
         strtab_offset: u64,
@@ -604,6 +1116,37 @@ impl<'a> ReadModuleMemory for ProcessModuleMemoryReader<'a> {
     }
 }
 
+/// An ELF image held in memory, such as an object copied out of the process.
+impl ReadModuleMemory for [u8] {
+    fn read(&self, offset: u64, length: u64) -> Result<Cow<'_, [u8]>, ModuleMemoryReadError> {
+        let inner = || {
+            use crate::module_reader::ReadError as E;
+            let start = usize::try_from(offset).map_err(|_| E::Overflow)?;
+            let length = usize::try_from(length).map_err(|_| E::Overflow)?;
+            let end = start.checked_add(length).ok_or(E::Overflow)?;
+            self.get(start..end)
+                .map(Cow::Borrowed)
+                .ok_or(E::OutOfBounds)
+        };
+
+        inner().map_err(|error| ModuleMemoryReadError {
+            start_address: None,
+            offset,
+            length,
+            error,
+        })
+    }
+    fn absolute_to_relative(&self, addr: u64) -> Option<u64> {
+        Some(addr)
+    }
+    fn relative_to_absolute(&self, addr: u64) -> Option<u64> {
+        Some(addr)
+    }
+    fn is_process_memory(&self) -> bool {
+        false
+    }
+}
+
 impl<T: ReadModuleMemory + ?Sized> ReadModuleMemory for &T {
     fn read(&self, offset: u64, length: u64) -> Result<Cow<'_, [u8]>, ModuleMemoryReadError> {
         T::read(self, offset, length)
@@ -741,6 +1284,39 @@ mod test {
         assert_eq!(soname, "libfoo.so.1");
     }
 
+    #[test]
+    fn module_files() {
+        let pid = std::process::id() as libc::pid_t;
+        let process_inspector = ProcessInspector::local(pid);
+        let module_files = ModuleFiles::new(&process_inspector, pid);
+
+        let exe = std::env::current_exe().unwrap();
+        let mappings = MappingInfo::for_pid(&process_inspector, pid, None).unwrap();
+        let mapping = mappings
+            .iter()
+            .find(|m| m.name.as_deref() == Some(exe.as_os_str()))
+            .expect("no mapping of the test executable");
+
+        let candidates = module_files.candidates(mapping);
+        let sources: Vec<_> = candidates.iter().map(|(_, source)| *source).collect();
+        assert_eq!(
+            sources,
+            [
+                ModuleFileSource::MapFiles,
+                ModuleFileSource::ProcessRoot,
+                ModuleFileSource::Path
+            ]
+        );
+        assert_eq!(
+            candidates[1].0,
+            Path::new(&format!("/proc/{pid}/root")).join(exe.strip_prefix("/").unwrap())
+        );
+
+        let expected = read_build_id_from_module(std::fs::read(&exe).unwrap().as_slice()).unwrap();
+        let build_id = read_build_id_from_file(&process_inspector, &module_files, mapping).unwrap();
+        assert_eq!(build_id, expected);
+    }
+
     pub struct SliceModuleMemoryReader<'a>(pub &'a [u8]);
 
     impl<'a> ReadModuleMemory for SliceModuleMemoryReader<'a> {
@@ -0,0 +1,307 @@
+//! Diagnoses why a process can't be dumped, which otherwise surfaces as a bare
+//! `PtraceAttachFailed(EPERM)`.
+
+use {
+    crate::{
+        Pid,
+        linux::{
+            maps_reader::MappingInfo,
+            process_inspection::{
+                self, ProcessInspector,
+                process_reader::{CopyFromProcessError, ProcessReader},
+            },
+        },
+    },
+    std::io::Read,
+};
+
+/// The bit of `CAP_SYS_PTRACE` in capability sets
+const CAP_SYS_PTRACE: u32 = 19;
+
+/// How a check of an operation on the process went
+#[derive(Debug, serde::Serialize)]
+pub enum Check<E> {
+    Passed,
+    Failed(E),
+    /// The check couldn't be run, e.g. because the process has no readable
+    /// memory or couldn't be attached to
+    Skipped,
+}
+
+impl<E> Check<E> {
+    pub fn passed(&self) -> bool {
+        matches!(self, Self::Passed)
+    }
+}
+
+impl<E> From<Result<(), E>> for Check<E> {
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::Passed,
+            Err(e) => Self::Failed(e),
+        }
+    }
+}
+
+/// The ways [`crate::process_reader`] can read the process's memory, in the
+/// order they are tried
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum ReaderStrategy {
+    /// `process_vm_readv`
+    VirtualMem,
+    /// `/proc/<pid>/mem`
+    File,
+    /// `ptrace(PTRACE_PEEKDATA)`
+    Ptrace,
+}
+
+/// The real, effective, saved and filesystem ids of a process, from the `Uid:`
+/// and `Gid:` lines of `/proc/<pid>/status`
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Credentials {
+    pub uids: [u32; 4],
+    pub gids: [u32; 4],
+}
+
+/// A likely cause of the process not being dumpable
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub enum Problem {
+    /// Yama's `ptrace_scope` is 3, so no process can be traced
+    YamaPtraceDisabled,
+    /// Yama's `ptrace_scope` is 2, so only processes with `CAP_SYS_PTRACE` can
+    /// trace
+    YamaAdminOnly,
+    /// Yama's `ptrace_scope` is 1, and the dumper isn't an ancestor of the
+    /// process, so the process must allow it with `prctl(PR_SET_PTRACER)`,
+    /// e.g. using `crash_context::set_ptracer`
+    YamaNotAncestor,
+    /// The process isn't dumpable, e.g. because it changed credentials or
+    /// called `prctl(PR_SET_DUMPABLE, 0)`, and the dumper lacks
+    /// `CAP_SYS_PTRACE`
+    NotDumpable,
+    /// The process runs with other ids than the dumper, and the dumper lacks
+    /// `CAP_SYS_PTRACE`
+    CredentialsMismatch,
+    /// Attaching failed for none of the other reasons, so one of the active
+    /// LSMs, e.g. SELinux or AppArmor, probably denied it
+    LsmDenial,
+}
+
+/// What [`probe`] found out about dumping a process
+#[derive(Debug, serde::Serialize)]
+pub struct ProbeReport {
+    pub pid: Pid,
+    /// `/proc/sys/kernel/yama/ptrace_scope`, or `None` if Yama isn't enabled
+    pub yama_ptrace_scope: Option<u32>,
+    /// Whether the dumper is an ancestor of the process, which is enough for
+    /// Yama's `ptrace_scope` 1
+    pub dumper_is_ancestor: bool,
+    /// Whether the process is dumpable
+    pub dumpable: Option<bool>,
+    pub dumper_credentials: Option<Credentials>,
+    pub process_credentials: Option<Credentials>,
+    /// Whether the dumper has `CAP_SYS_PTRACE`
+    pub dumper_has_cap_sys_ptrace: Option<bool>,
+    /// The active LSMs, from `/sys/kernel/security/lsm`
+    pub lsms: Vec<String>,
+    /// The LSM context of the dumper, from `/proc/self/attr/current`
+    pub dumper_security_context: Option<String>,
+    /// The LSM context of the process, from `/proc/<pid>/attr/current`
+    pub process_security_context: Option<String>,
+    pub ptrace_attach: Check<process_inspection::Error>,
+    pub process_vm_readv: Check<CopyFromProcessError>,
+    pub proc_mem: Check<CopyFromProcessError>,
+    /// Only checked if `ptrace_attach` passed, as it needs a stopped tracee
+    pub ptrace_peekdata: Check<CopyFromProcessError>,
+    /// The strategy [`crate::process_reader`] would read memory with, if any
+    /// works
+    pub selected_strategy: Option<ReaderStrategy>,
+    /// The likely causes of the process not being dumpable
+    pub problems: Vec<Problem>,
+}
+
+/// Checks whether the process `pid` can be dumped and, if not, why.
+///
+/// This briefly attaches to the main thread of the process with ptrace.
+pub fn probe(pid: Pid) -> ProbeReport {
+    let pi = ProcessInspector::local(pid);
+
+    let yama_ptrace_scope = read_to_string(&pi, "/proc/sys/kernel/yama/ptrace_scope")
+        .and_then(|s| s.trim().parse().ok());
+    let dumper_status = read_to_string(&pi, "/proc/self/status");
+    let process_status = read_to_string(&pi, format!("/proc/{pid}/status"));
+    let dumper_credentials = dumper_status.as_deref().and_then(credentials);
+    let process_credentials = process_status.as_deref().and_then(credentials);
+    let dumper_has_cap_sys_ptrace = dumper_status.as_deref().and_then(|status| {
+        let cap_eff = status_field(status, "CapEff:")?;
+        let cap_eff = u64::from_str_radix(cap_eff, 16).ok()?;
+        Some(cap_eff & (1 << CAP_SYS_PTRACE) != 0)
+    });
+
+    // The procfs files of a process that isn't dumpable belong to root rather
+    // than to its effective uid
+    let dumpable = process_credentials.as_ref().and_then(|creds| {
+        let stat = pi.stat_file(format!("/proc/{pid}")).ok()?;
+        Some(stat.st_uid == creds.uids[1])
+    });
+
+    let lsms = read_to_string(&pi, "/sys/kernel/security/lsm")
+        .map(|s| {
+            s.trim_end_matches(['\0', '\n'])
+                .split(',')
+                .filter(|lsm| !lsm.is_empty())
+                .map(str::to_owned)
+                .collect()
+        })
+        .unwrap_or_default();
+    let security_context = |path: String| {
+        read_to_string(&pi, path)
+            .map(|s| s.trim_end_matches(['\0', '\n']).to_owned())
+            .filter(|s| !s.is_empty())
+    };
+    let dumper_security_context = security_context("/proc/self/attr/current".into());
+    let process_security_context = security_context(format!("/proc/{pid}/attr/current"));
+
+    let address = MappingInfo::for_pid(&pi, pid, None)
+        .ok()
+        .and_then(|mappings| {
+            mappings
+                .iter()
+                .find(|m| {
+                    m.permissions
+                        .contains(procfs_core::process::MMPermissions::READ)
+                })
+                .map(|m| m.start_address)
+        });
+    let check_read = |reader: &ProcessReader| match address {
+        Some(address) => reader.read(address, &mut [0u8; 8]).map(drop).into(),
+        None => Check::Skipped,
+    };
+
+    let process_vm_readv = check_read(&ProcessReader::for_virtual_mem(&pi));
+    let proc_mem = match ProcessReader::for_file(&pi) {
+        Ok(reader) => check_read(&reader),
+        Err(e) => Check::Failed(CopyFromProcessError::Backend(e)),
+    };
+    let ptrace_attach: Check<_> = pi.suspend_thread(pid).into();
+    let ptrace_peekdata = if ptrace_attach.passed() {
+        let check = check_read(&ProcessReader::for_ptrace(&pi));
+        if let Err(e) = pi.resume_thread(pid) {
+            log::warn!("failed to detach from {pid} after probing it: {e}");
+        }
+        check
+    } else {
+        Check::Skipped
+    };
+
+    let selected_strategy = [
+        (&process_vm_readv, ReaderStrategy::VirtualMem),
+        (&proc_mem, ReaderStrategy::File),
+        (&ptrace_peekdata, ReaderStrategy::Ptrace),
+    ]
+    .into_iter()
+    .find_map(|(check, strategy)| check.passed().then_some(strategy));
+
+    let mut report = ProbeReport {
+        pid,
+        yama_ptrace_scope,
+        dumper_is_ancestor: is_ancestor(&pi, pid),
+        dumpable,
+        dumper_credentials,
+        process_credentials,
+        dumper_has_cap_sys_ptrace,
+        lsms,
+        dumper_security_context,
+        process_security_context,
+        ptrace_attach,
+        process_vm_readv,
+        proc_mem,
+        ptrace_peekdata,
+        selected_strategy,
+        problems: Vec::new(),
+    };
+    report.problems = report.find_problems();
+    report
+}
+
+impl ProbeReport {
+    fn find_problems(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let privileged = self.dumper_has_cap_sys_ptrace == Some(true);
+
+        match self.yama_ptrace_scope {
+            Some(3) => problems.push(Problem::YamaPtraceDisabled),
+            Some(2) if !privileged => problems.push(Problem::YamaAdminOnly),
+            // Whether the process allowed the dumper with PR_SET_PTRACER can't
+            // be read, so this is only a problem if attaching failed
+            Some(1) if !privileged && !self.dumper_is_ancestor && !self.ptrace_attach.passed() => {
+                problems.push(Problem::YamaNotAncestor)
+            }
+            _ => {}
+        }
+        if !privileged {
+            if self.dumpable == Some(false) {
+                problems.push(Problem::NotDumpable);
+            }
+            // Attaching compares the dumper's real ids to the process's real,
+            // effective and saved ids
+            if let (Some(dumper), Some(process)) =
+                (&self.dumper_credentials, &self.process_credentials)
+                && (process.uids[..3].iter().any(|&uid| uid != dumper.uids[0])
+                    || process.gids[..3].iter().any(|&gid| gid != dumper.gids[0]))
+            {
+                problems.push(Problem::CredentialsMismatch);
+            }
+        }
+        if problems.is_empty() && !self.ptrace_attach.passed() && !self.lsms.is_empty() {
+            problems.push(Problem::LsmDenial);
+        }
+
+        problems
+    }
+}
+
+fn read_to_string(pi: &ProcessInspector, path: impl Into<std::path::PathBuf>) -> Option<String> {
+    let mut s = String::new();
+    pi.read_file(path).ok()?.read_to_string(&mut s).ok()?;
+    Some(s)
+}
+
+fn status_field<'a>(status: &'a str, name: &str) -> Option<&'a str> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(name))
+        .map(str::trim)
+}
+
+fn credentials(status: &str) -> Option<Credentials> {
+    let ids = |name| -> Option<[u32; 4]> {
+        let ids: Vec<u32> = status_field(status, name)?
+            .split_whitespace()
+            .map(|id| id.parse().ok())
+            .collect::<Option<_>>()?;
+        ids.try_into().ok()
+    };
+    Some(Credentials {
+        uids: ids("Uid:")?,
+        gids: ids("Gid:")?,
+    })
+}
+
+/// Whether the dumper is an ancestor of the process `pid`
+fn is_ancestor(pi: &ProcessInspector, pid: Pid) -> bool {
+    let dumper = std::process::id() as Pid;
+    let mut pid = pid;
+    while pid > 1 {
+        let Some(ppid) = read_to_string(pi, format!("/proc/{pid}/status"))
+            .and_then(|status| status_field(&status, "PPid:")?.parse().ok())
+        else {
+            return false;
+        };
+        if ppid == dumper {
+            return true;
+        }
+        pid = ppid;
+    }
+    false
+}
@@ -8,11 +8,14 @@ use std::{
     borrow::Cow,
     ffi::{CString, OsString},
     io,
-    os::unix::ffi::OsStringExt,
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::ffi::OsStringExt,
+    },
     path::PathBuf,
 };
 
-pub use process_backend::regs;
+pub use process_backend::{local::PreopenedDirs, regs};
 
 pub mod process_reader;
 
@@ -43,9 +46,43 @@ impl ProcessInspector {
             },
         }
     }
+    /// The process being inspected
+    pub fn pid(&self) -> libc::pid_t {
+        self.pid
+    }
+
+    /// Creates an inspector that accesses the filesystem only through the given directories
+    pub fn local_with_preopened_dirs(pid: libc::pid_t, dirs: PreopenedDirs) -> Self {
+        let backend = local::Backend::with_preopened_dirs(pid, dirs);
+        let process_reader_backend = backend.process_reader();
+
+        ProcessInspector {
+            pid,
+            backend: Backend::Local {
+                backend,
+                process_reader_backend,
+            },
+        }
+    }
     pub fn process_reader(&self) -> ProcessReader<'_> {
         ProcessReader::new(self)
     }
+    /// Opens the process, so that it can't be confused with another process reusing its pid later
+    /// on. Fails with an error for which [`Error::is_process_gone`] is true if the process no
+    /// longer exists.
+    pub fn open_process(&mut self) -> Result<(), Error> {
+        match &mut self.backend {
+            Backend::Local {
+                backend,
+                process_reader_backend,
+            } => {
+                backend.open_process().map_err(Error::Local)?;
+                // Read `/proc/<pid>/mem` through the directory just opened
+                *process_reader_backend = backend.process_reader();
+                Ok(())
+            }
+        }
+    }
     pub fn stop_process(&self) -> Result<(), Error> {
         failspot!(if StopProcess {
             return Err(Error::Local(local::Error::SigStopFailed(libc::EPERM)));
@@ -68,6 +105,23 @@ impl ProcessInspector {
         }
     }
 
+    pub fn interrupt_thread(&self, tid: libc::pid_t) -> Result<(), Error> {
+        if failspot!(InterruptThread) {
+            // Let PTRACE_SEIZE succeed, and fail PTRACE_INTERRUPT
+            self.fail_nth_syscall_with(1, libc::EPERM);
+        }
+
+        match &self.backend {
+            Backend::Local { backend, .. } => backend.interrupt_thread(tid).map_err(Error::Local),
+        }
+    }
+
+    pub fn wait_for_interrupt(&self, tid: libc::pid_t) -> Result<(), Error> {
+        match &self.backend {
+            Backend::Local { backend, .. } => backend.wait_for_interrupt(tid).map_err(Error::Local),
+        }
+    }
+
     pub fn resume_thread(&self, tid: libc::pid_t) -> Result<(), Error> {
         match &self.backend {
             Backend::Local { backend, .. } => backend.resume_thread(tid).map_err(Error::Local),
@@ -177,6 +231,14 @@ impl io::Read for FileReader {
     }
 }
 
+impl AsRawFd for FileReader {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Self::Local(l) => l.as_raw_fd(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum DirReader {
     Local(local::DirReader),
@@ -202,6 +264,12 @@ impl ProcessInspector {
             Backend::Local { backend, .. } => backend.fail_one_syscall_with(errno),
         }
     }
+
+    pub fn fail_nth_syscall_with(&self, skip: usize, errno: c_int) {
+        match &self.backend {
+            Backend::Local { backend, .. } => backend.fail_nth_syscall_with(skip, errno),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -255,3 +323,28 @@ pub enum Error {
     #[error("an error occurred running a syscall directly")]
     Local(#[source] local::Error),
 }
+
+impl Error {
+    /// Whether the error is due to the thread not having been seized with `PTRACE_SEIZE`, which
+    /// leaves it untraced.
+    pub fn is_seize_failure(&self) -> bool {
+        matches!(self, Self::Local(local::Error::PtraceSeizeFailed(_)))
+    }
+
+    /// Whether the error is due to the process having exited.
+    pub fn is_process_gone(&self) -> bool {
+        matches!(self, Self::Local(local::Error::ProcessGone))
+    }
+
+    /// Whether the error is due to a file or directory not existing.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::Local(
+                local::Error::OpenFileFailed(libc::ENOENT)
+                    | local::Error::OpenDirFailed(libc::ENOENT)
+                    | local::Error::StatFailed(libc::ENOENT)
+            )
+        )
+    }
+}
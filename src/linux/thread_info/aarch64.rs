@@ -34,6 +34,28 @@ impl ThreadInfoAarch64 {
         out.float_regs[..FP_REG_COUNT].copy_from_slice(&self.fpregs.vregs[..FP_REG_COUNT]);
     }
 
+    /// Creates the info of a thread of which only the stack and instruction
+    /// pointers are known, with all other registers zeroed
+    pub fn from_pointers(
+        tgid: Pid,
+        ppid: Pid,
+        stack_pointer: usize,
+        instruction_pointer: usize,
+    ) -> Self {
+        // SAFETY: plain C structs
+        let mut regs: libc::user_regs_struct = unsafe { std::mem::zeroed() };
+        regs.sp = stack_pointer as u64;
+        regs.pc = instruction_pointer as u64;
+
+        Self {
+            stack_pointer,
+            tgid,
+            ppid,
+            regs,
+            fpregs: unsafe { std::mem::zeroed() },
+        }
+    }
+
     pub fn create(process_inspector: &ProcessInspector, tid: Pid) -> Result<Self, ThreadInfoError> {
         let (ppid, tgid) = super::get_ppid_and_tgid(process_inspector, tid)?;
         let regs = process_inspector
@@ -27,6 +27,28 @@ impl ThreadInfoArm {
         out.float_save.regs = self.fpregs.fpregs;
     }
 
+    /// Creates the info of a thread of which only the stack and instruction
+    /// pointers are known, with all other registers zeroed
+    pub fn from_pointers(
+        tgid: Pid,
+        ppid: Pid,
+        stack_pointer: usize,
+        instruction_pointer: usize,
+    ) -> Self {
+        // SAFETY: plain C structs
+        let mut regs: user_regs_struct = unsafe { std::mem::zeroed() };
+        regs.uregs[13] = stack_pointer as _;
+        regs.uregs[15] = instruction_pointer as _;
+
+        ThreadInfoArm {
+            stack_pointer,
+            tgid,
+            ppid,
+            regs,
+            fpregs: unsafe { std::mem::zeroed() },
+        }
+    }
+
     pub fn create(process_inspector: &ProcessInspector, tid: Pid) -> Result<Self, ThreadInfoError> {
         let (ppid, tgid) = super::get_ppid_and_tgid(process_inspector, tid)?;
         let regs = process_inspector
@@ -18,7 +18,7 @@ pub enum ThreadInfoError {
     IndexOutOfBounds(usize, usize),
     #[error("Either ppid ({1}) or tgid ({2}) not found in {0}")]
     InvalidPid(String, Pid, Pid),
-    #[error("failed reading /proc/<tid>/status")]
+    #[error("failed reading /proc/<pid>/task/<tid>/status")]
     ReadFileFailed(#[source] process_inspection::Error),
     #[error("IO error")]
     IOError(
@@ -34,8 +34,34 @@ pub enum ThreadInfoError {
     ),
     #[error("ptrace error")]
     PtraceError(#[source] process_inspection::Error),
-    #[error("Invalid line in /proc/{0}/status: {1}")]
+    #[error("Invalid line in the status file of thread {0}: {1}")]
     InvalidProcStatusFile(Pid, String),
+    #[error("failed reading {0}")]
+    ReadThreadFileFailed(String, #[source] process_inspection::Error),
+    #[error(
+        "Neither the stat nor the syscall file of thread {0} expose its stack and instruction pointers"
+    )]
+    PointersUnavailable(Pid),
+}
+
+/// Where the stack and instruction pointers of a thread that isn't attached to
+/// with ptrace were read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerSource {
+    /// The `kstkesp` and `kstkeip` fields of `/proc/<pid>/task/<tid>/stat`,
+    /// which most kernels only expose while the process dumps core
+    Stat,
+    /// The last two fields of `/proc/<pid>/task/<tid>/syscall`, exposed while
+    /// the thread is blocked
+    Syscall,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct ThreadPointers {
+    pub stack_pointer: usize,
+    pub instruction_pointer: usize,
+    pub source: PointerSource,
 }
 
 cfg_if::cfg_if! {
@@ -54,11 +80,86 @@ cfg_if::cfg_if! {
     }
 }
 
+impl ThreadInfo {
+    /// Creates the info of a thread that isn't attached to with ptrace, of
+    /// which only the stack and instruction pointers are known.
+    pub fn create_without_ptrace(
+        process_inspector: &ProcessInspector,
+        pid: Pid,
+        tid: Pid,
+    ) -> Result<Self> {
+        let (ppid, tgid) = get_ppid_and_tgid(process_inspector, tid)?;
+        let pointers = read_thread_pointers(process_inspector, pid, tid)?;
+        Ok(Self::from_pointers(
+            tgid,
+            ppid,
+            pointers.stack_pointer,
+            pointers.instruction_pointer,
+        ))
+    }
+}
+
+/// Reads the stack and instruction pointers of a thread from procfs, which
+/// needs no ptrace.
+pub fn read_thread_pointers(
+    process_inspector: &ProcessInspector,
+    pid: Pid,
+    tid: Pid,
+) -> Result<ThreadPointers> {
+    let read = |path: String| -> Result<String> {
+        let mut contents = String::new();
+        io::Read::read_to_string(
+            &mut process_inspector
+                .read_file(&path)
+                .map_err(|e| ThreadInfoError::ReadThreadFileFailed(path, e))?,
+            &mut contents,
+        )?;
+        Ok(contents)
+    };
+
+    // The fields after the command name, which may contain spaces, start with
+    // the third
+    const KSTKESP: usize = 29 - 3;
+    const KSTKEIP: usize = 30 - 3;
+    let stat = read(format!("/proc/{pid}/task/{tid}/stat"))?;
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .map(|(_, fields)| fields.split_whitespace().collect())
+        .unwrap_or_default();
+    if let (Some(sp), Some(ip)) = (fields.get(KSTKESP), fields.get(KSTKEIP)) {
+        let (stack_pointer, instruction_pointer) = (sp.parse()?, ip.parse()?);
+        if stack_pointer != 0 && instruction_pointer != 0 {
+            return Ok(ThreadPointers {
+                stack_pointer,
+                instruction_pointer,
+                source: PointerSource::Stat,
+            });
+        }
+    }
+
+    // `<nr> <args>... <sp> <pc>`, or `-1 <sp> <pc>` outside of a syscall, or
+    // `running`
+    let syscall = read(format!("/proc/{pid}/task/{tid}/syscall"))?;
+    let fields: Vec<&str> = syscall.split_whitespace().collect();
+    match fields[..] {
+        [_, .., sp, pc] => {
+            let parse = |field: &str| usize::from_str_radix(field.trim_start_matches("0x"), 16);
+            Ok(ThreadPointers {
+                stack_pointer: parse(sp)?,
+                instruction_pointer: parse(pc)?,
+                source: PointerSource::Syscall,
+            })
+        }
+        _ => Err(ThreadInfoError::PointersUnavailable(tid)),
+    }
+}
+
 fn get_ppid_and_tgid(process_inspector: &ProcessInspector, tid: Pid) -> Result<(Pid, Pid)> {
     let mut ppid = -1;
     let mut tgid = -1;
 
-    let status_path = path::PathBuf::from(format!("/proc/{tid}/status"));
+    let pid = process_inspector.pid();
+    let status_path = path::PathBuf::from(format!("/proc/{pid}/task/{tid}/status"));
     let status_file = process_inspector
         .read_file(status_path)
         .map_err(ThreadInfoError::ReadFileFailed)?;
@@ -85,7 +186,7 @@ fn get_ppid_and_tgid(process_inspector: &ProcessInspector, tid: Pid) -> Result<(
     }
     if ppid == -1 || tgid == -1 {
         return Err(ThreadInfoError::InvalidPid(
-            format!("/proc/{tid}/status"),
+            format!("/proc/{pid}/task/{tid}/status"),
             ppid,
             tgid,
         ));
@@ -65,6 +65,39 @@ impl ThreadInfoX86 {
         })
     }
 
+    /// Creates the info of a thread of which only the stack and instruction
+    /// pointers are known, with all other registers zeroed
+    pub fn from_pointers(
+        tgid: Pid,
+        ppid: Pid,
+        stack_pointer: usize,
+        instruction_pointer: usize,
+    ) -> Self {
+        // SAFETY: plain C structs
+        let mut regs: user_regs_struct = unsafe { mem::zeroed() };
+        #[cfg(target_arch = "x86_64")]
+        {
+            regs.rsp = stack_pointer as _;
+            regs.rip = instruction_pointer as _;
+        }
+        #[cfg(target_arch = "x86")]
+        {
+            regs.esp = stack_pointer as _;
+            regs.eip = instruction_pointer as _;
+        }
+
+        Self {
+            stack_pointer,
+            tgid,
+            ppid,
+            regs,
+            fpregs: unsafe { mem::zeroed() },
+            dregs: [0; NUM_DEBUG_REGISTERS],
+            #[cfg(target_arch = "x86")]
+            fpxregs: unsafe { mem::zeroed() },
+        }
+    }
+
     #[cfg(target_arch = "x86_64")]
     pub fn get_instruction_pointer(&self) -> usize {
         self.regs.rip as usize
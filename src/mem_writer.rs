@@ -287,3 +287,19 @@ pub fn write_string_to_location(
 
     Ok(location)
 }
+
+/// Writes a `MINIDUMP_UTF8_STRING`: the length in bytes followed by the
+/// nul-terminated UTF-8 data.
+pub fn write_utf8_string_to_location(
+    buffer: &mut Buffer,
+    text: &str,
+) -> WriteResult<MDLocationDescriptor> {
+    let text_header = MemoryWriter::<u32>::alloc_with_val(buffer, text.len().try_into()?)?;
+    buffer.write_all(text.as_bytes());
+    buffer.write_all(&[0]);
+
+    let mut location = text_header.location();
+    location.data_size += text.len() as u32 + 1;
+
+    Ok(location)
+}
@@ -1,15 +1,19 @@
 pub use minidump_common::format::{
-    self, ArmElfHwCaps as MDCPUInformationARMElfHwCaps, GUID, MINIDUMP_DIRECTORY as MDRawDirectory,
+    self, ArmElfHwCaps as MDCPUInformationARMElfHwCaps, GUID,
+    MINIDUMP_CRASHPAD_INFO as MDRawCrashpadInfo, MINIDUMP_DIRECTORY as MDRawDirectory,
     MINIDUMP_EXCEPTION as MDException, MINIDUMP_EXCEPTION_STREAM as MDRawExceptionStream,
     MINIDUMP_HANDLE_DATA_STREAM as MDRawHandleDataStream,
     MINIDUMP_HANDLE_DESCRIPTOR as MDRawHandleDescriptor, MINIDUMP_HEADER as MDRawHeader,
     MINIDUMP_LOCATION_DESCRIPTOR as MDLocationDescriptor,
     MINIDUMP_MEMORY_DESCRIPTOR as MDMemoryDescriptor, MINIDUMP_MEMORY_INFO as MDMemoryInfo,
     MINIDUMP_MEMORY_INFO_LIST as MDMemoryInfoList, MINIDUMP_MODULE as MDRawModule,
-    MINIDUMP_SIGNATURE as MD_HEADER_SIGNATURE, MINIDUMP_STREAM_TYPE as MDStreamType,
-    MINIDUMP_SYSTEM_INFO as MDRawSystemInfo, MINIDUMP_THREAD as MDRawThread,
-    MINIDUMP_THREAD_NAME as MDRawThreadName, MINIDUMP_VERSION as MD_HEADER_VERSION, PlatformId,
-    ProcessorArchitecture as MDCPUArchitecture, VS_FIXEDFILEINFO as MDVSFixedFileInfo,
+    MINIDUMP_SIGNATURE as MD_HEADER_SIGNATURE,
+    MINIDUMP_SIMPLE_STRING_DICTIONARY as MDRawSimpleStringDictionary,
+    MINIDUMP_SIMPLE_STRING_DICTIONARY_ENTRY as MDRawSimpleStringDictionaryEntry,
+    MINIDUMP_STREAM_TYPE as MDStreamType, MINIDUMP_SYSTEM_INFO as MDRawSystemInfo,
+    MINIDUMP_THREAD as MDRawThread, MINIDUMP_THREAD_NAME as MDRawThreadName,
+    MINIDUMP_VERSION as MD_HEADER_VERSION, PlatformId, ProcessorArchitecture as MDCPUArchitecture,
+    VS_FIXEDFILEINFO as MDVSFixedFileInfo,
 };
 
 /* An MDRVA is an offset into the minidump file.  The beginning of the
@@ -35,3 +39,53 @@ cfg_if::cfg_if! {
         pub use format::DSO_DEBUG_32 as MDRawDebug;
     }
 }
+
+/// Stream types for streams written by minidump-writer that have no assigned
+/// type in `minidump-common`.
+///
+/// They use the `0x4d77` ("Mw") prefix so they can't collide with the Breakpad
+/// (`0x4767`), Crashpad (`0x4350`) or Mozilla (`0x4d7a`) ranges.
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MDExtStreamType {
+    /// JSON describing the signal state of the crashing thread: `uc_flags`,
+    /// the saved signal mask and the alternate signal stack
+    LinuxSignalContext = 0x4d770001,
+    /// JSON classifying the fault of the crashing thread, see
+    /// `exception_information` in the exception stream for the same
+    /// information in compact form
+    LinuxFaultDiagnosis = 0x4d770002,
+    /// JSON containing every field of the `signalfd_siginfo` of the crash
+    LinuxSigInfo = 0x4d770003,
+    /// The message glibc recorded before aborting the process, e.g. for a
+    /// failed `assert()`, as UTF-8 text
+    LinuxAbortMessage = 0x4d770004,
+    /// The entries of the process's `/tmp/perf-<pid>.map` that lie in
+    /// anonymous executable mappings, in the same `START SIZE name` text
+    /// format
+    LinuxPerfMap = 0x4d770005,
+    /// JSON listing the `.gnu_debuglink` file name and CRC of each module
+    /// that has one, and where its `.gnu_debugdata` section was copied to
+    LinuxDebugInfo = 0x4d770006,
+    /// JSON naming the nearest preceding exported function of each thread's
+    /// instruction pointer and of the code addresses on the crashing thread's
+    /// stack
+    LinuxSymbolHints = 0x4d770007,
+    /// JSON describing the file each module was mapped from and the file now
+    /// at its path, and whether the module was deleted or replaced on disk
+    LinuxModuleFiles = 0x4d770008,
+    /// JSON identifying the container of the process: its ids and those of
+    /// its threads in each PID namespace, its cgroups, the inode numbers of
+    /// its namespaces and its hostname
+    LinuxContainer = 0x4d770009,
+    /// JSON marking a dump as degraded, e.g. written without ptrace, so that
+    /// its thread contexts only hold the stack and instruction pointers
+    LinuxDegradedDump = 0x4d77000a,
+    /// JSON listing the threads that exited while the dump was being
+    /// captured, so that they can be told apart from threads that never
+    /// existed
+    LinuxExitedThreads = 0x4d77000b,
+    /// JSON listing how many bytes of each region of the memory info list are
+    /// resident, swapped out and anonymous, from `/proc/<pid>/smaps`
+    LinuxMemoryUsage = 0x4d77000c,
+}
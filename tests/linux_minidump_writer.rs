@@ -9,7 +9,11 @@ use {
         CrashContextExt, Pid,
         app_memory::AppMemory,
         maps_reader::{MappingEntry, MappingInfo, SystemMappingInfo},
-        minidump_writer::{MinidumpWriter, MinidumpWriterConfig, errors::WriterError},
+        minidump_format::MDExtStreamType,
+        minidump_writer::{
+            MinidumpWriter, MinidumpWriterConfig, PerfMapOptions, RequestedException,
+            errors::WriterError,
+        },
         module_reader::{self},
     },
     procfs_core::process::MMPermissions,
@@ -806,3 +810,1311 @@ fn memory_info_list_stream() {
     let list: MinidumpMemoryInfoList = dump.get_stream().expect("no memory info list");
     assert!(list.iter().count() > 1);
 }
+
+#[test]
+fn maps_snapshot() {
+    for smaps in [false, true] {
+        let mut child = start_child_and_wait_for_threads(1);
+        let pid = child.id() as i32;
+
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("maps_snapshot")
+            .tempfile()
+            .unwrap();
+
+        let mut config = MinidumpWriterConfig::new(pid, pid);
+        if smaps {
+            config.capture_smaps();
+        }
+        config
+            .write(&mut tmpfile)
+            .expect("cound not write minidump");
+        child.kill().expect("Failed to kill process");
+        child.wait().expect("Failed to wait on killed process");
+
+        let dump = Minidump::read_path(tmpfile.path()).expect("failed to read minidump");
+        let maps = String::from_utf8(dump.get_raw_stream(LinuxMaps.into()).unwrap().to_vec())
+            .expect("maps aren't UTF-8");
+        let starts: Vec<u64> = maps
+            .lines()
+            .map(|line| {
+                let (start, _) = line.split_once('-').expect("not a maps line");
+                u64::from_str_radix(start, 16).expect("not a maps line")
+            })
+            .collect();
+
+        // The memory info list and the modules describe the same maps
+        let list: MinidumpMemoryInfoList = dump.get_stream().expect("no memory info list");
+        let bases: Vec<u64> = list.iter().map(|info| info.raw.base_address).collect();
+        assert_eq!(bases, starts);
+
+        let modules: MinidumpModuleList = dump.get_stream().expect("no module list");
+        for module in modules.iter() {
+            assert!(
+                starts.contains(&module.base_address()),
+                "{} isn't in the maps",
+                module.name
+            );
+        }
+    }
+}
+
+#[test]
+fn memory_info_from_smaps() {
+    use minidump_common::format::{MemoryProtection, MemoryState};
+
+    let mut child = start_child_and_wait_for_threads(3);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("memory_info_from_smaps")
+        .tempfile()
+        .unwrap();
+
+    let mut config = MinidumpWriterConfig::new(pid, pid);
+    config.capture_smaps();
+    config
+        .write(&mut tmpfile)
+        .expect("cound not write minidump");
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("failed to read minidump");
+    let list: MinidumpMemoryInfoList = dump.get_stream().expect("no memory info list");
+    let infos: Vec<_> = list.iter().map(|info| &info.raw).collect();
+
+    // The stacks of the two spawned threads are allocated along with a guard
+    // below them
+    let mut guards = 0;
+    for pair in infos.windows(2) {
+        let (guard, stack) = (pair[0], pair[1]);
+        if guard.protection & MemoryProtection::PAGE_GUARD.bits() == 0 {
+            continue;
+        }
+        assert_eq!(guard.state, MemoryState::MEM_RESERVE.bits());
+        assert_eq!(stack.base_address, guard.base_address + guard.region_size);
+        assert_eq!(stack.allocation_base, guard.base_address);
+        guards += 1;
+    }
+    assert!(guards >= 2, "found {guards} stack guards");
+
+    let modules: MinidumpModuleList = dump.get_stream().expect("no module list");
+    let main_module = modules.main_module().expect("no main module");
+    let main_range = main_module.base_address()..main_module.base_address() + main_module.size();
+    let main_regions: Vec<_> = infos
+        .iter()
+        .filter(|info| main_range.contains(&info.base_address))
+        .collect();
+    assert!(main_regions.len() > 1);
+    for info in main_regions {
+        assert_eq!(info.allocation_base, main_module.base_address());
+    }
+
+    let usage: serde_json::Value = serde_json::from_slice(
+        dump.get_raw_stream(MDExtStreamType::LinuxMemoryUsage as u32)
+            .expect("no memory usage stream"),
+    )
+    .unwrap();
+    let bases: Vec<u64> = usage["regions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|region| region["base_address"].as_u64().unwrap())
+        .collect();
+    assert_eq!(
+        bases,
+        infos
+            .iter()
+            .map(|info| info.base_address)
+            .collect::<Vec<_>>()
+    );
+    assert!(usage["total"]["rss"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn signal_context_stream() {
+    let mut child = start_child_and_return(&["spawn_alloc_wait"]);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("signal_context_stream")
+        .tempfile()
+        .unwrap();
+
+    let mut f = BufReader::new(child.stdout.as_mut().expect("Can't open stdout"));
+    let mut buf = String::new();
+    let _ = f
+        .read_line(&mut buf)
+        .expect("Couldn't read address provided by child");
+    let mut output = buf.split_whitespace();
+    let memory_addr = usize::from_str_radix(output.next().unwrap().trim_start_matches("0x"), 16)
+        .expect("unable to parse mmap_addr");
+    let memory_size: usize = output
+        .next()
+        .unwrap()
+        .parse()
+        .expect("unable to parse memory_size");
+
+    // Pretend the child's allocation is the alternate signal stack
+    let mut crash_context = get_crash_context(pid);
+    crash_context.inner.context.uc_stack.ss_sp = memory_addr as _;
+    crash_context.inner.context.uc_stack.ss_flags = 0;
+    crash_context.inner.context.uc_stack.ss_size = memory_size;
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.set_crash_context(crash_context);
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+
+    let section: MinidumpMemoryList = dump.get_stream().expect("Couldn't find MinidumpMemoryList");
+    let region = section
+        .memory_at_address(memory_addr as u64)
+        .expect("Couldn't find sigaltstack region");
+    assert_eq!(region.base_address, memory_addr as u64);
+    assert_eq!(region.size, memory_size as u64);
+    let values: Vec<u8> = (0..memory_size).map(|idx| (idx % 255) as u8).collect();
+    assert_eq!(region.bytes, values);
+
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxSignalContext as u32)
+        .expect("Couldn't find LinuxSignalContext");
+    let signal_context: serde_json::Value =
+        serde_json::from_slice(stream).expect("signal context is not JSON");
+    assert_eq!(
+        signal_context["sigaltstack"],
+        json!({
+            "ss_sp": memory_addr,
+            "ss_flags": 0,
+            "ss_size": memory_size,
+            "captured": true,
+        })
+    );
+    assert!(signal_context["uc_sigmask"].is_u64());
+    assert!(signal_context["handler_stack_pointer"].is_u64());
+}
+
+fn set_crash_stack_pointer(crash_context: &mut CrashContextExt, sp: usize) {
+    let mcontext = &mut crash_context.inner.context.uc_mcontext;
+    #[cfg(target_arch = "x86_64")]
+    {
+        mcontext.gregs[libc::REG_RSP as usize] = sp as _;
+    }
+    #[cfg(target_arch = "x86")]
+    {
+        mcontext.gregs[libc::REG_ESP as usize] = sp as _;
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        mcontext.sp = sp as _;
+    }
+    #[cfg(target_arch = "arm")]
+    {
+        mcontext.arm_sp = sp as _;
+    }
+}
+
+#[test]
+fn fault_diagnosis() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let maps = std::fs::read_to_string(format!("/proc/{pid}/maps")).unwrap();
+    let stack_start = maps
+        .lines()
+        .find(|line| line.ends_with("[stack]"))
+        .and_then(|line| line.split('-').next())
+        .map(|start| usize::from_str_radix(start, 16).unwrap())
+        .expect("child has no [stack] mapping");
+
+    let diagnose = |fault_address: usize, stack_pointer: usize| {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("fault_diagnosis")
+            .tempfile()
+            .unwrap();
+
+        let mut crash_context = get_crash_context(pid);
+        crash_context.inner.siginfo.ssi_signo = libc::SIGSEGV as u32;
+        crash_context.inner.siginfo.ssi_addr = fault_address as u64;
+        set_crash_stack_pointer(&mut crash_context, stack_pointer);
+
+        let mut tmp = MinidumpWriterConfig::new(pid, pid);
+        tmp.set_crash_context(crash_context);
+        tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+        let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+        let exception: MinidumpException = dump.get_stream().expect("Couldn't find exception");
+        let stream = dump
+            .get_raw_stream(MDExtStreamType::LinuxFaultDiagnosis as u32)
+            .expect("Couldn't find LinuxFaultDiagnosis");
+        let diagnosis: serde_json::Value =
+            serde_json::from_slice(stream).expect("fault diagnosis is not JSON");
+        (exception.raw.exception_record, diagnosis)
+    };
+
+    let (record, diagnosis) = diagnose(0x10, stack_start + 0x100);
+    assert_eq!(record.number_parameters, 10);
+    assert_eq!(record.exception_information[0], 2);
+    assert_eq!(diagnosis["class"], "NullDereference");
+
+    let (record, diagnosis) = diagnose(stack_start - 0x1000, stack_start + 0x100);
+    assert_eq!(record.exception_information[0], 1);
+    assert_eq!(record.exception_information[1], stack_start as u64);
+    assert_eq!(record.exception_information[2], 0x1000);
+    assert_eq!(diagnosis["class"], "StackOverflow");
+    assert_eq!(diagnosis["mapping"]["name"], "[stack]");
+    assert_eq!(diagnosis["distance_below_stack"], 0x1000);
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+}
+
+#[test]
+fn siginfo_stream() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("siginfo_stream")
+        .tempfile()
+        .unwrap();
+
+    // A seccomp violation
+    let mut crash_context = get_crash_context(pid);
+    let siginfo = &mut crash_context.inner.siginfo;
+    siginfo.ssi_signo = libc::SIGSYS as u32;
+    siginfo.ssi_code = 1; // SYS_SECCOMP
+    siginfo.ssi_errno = -libc::EPERM;
+    siginfo.ssi_syscall = libc::SYS_getpid as i32;
+    siginfo.ssi_arch = 0xc000003e;
+    siginfo.ssi_call_addr = 0x1234;
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.set_crash_context(crash_context);
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let exception: MinidumpException = dump.get_stream().expect("Couldn't find exception");
+    let record = exception.raw.exception_record;
+    assert_eq!(record.number_parameters, 10);
+    assert_eq!(record.exception_information[3], -libc::EPERM as u64);
+    assert_eq!(record.exception_information[7], libc::SYS_getpid as u64);
+    assert_eq!(record.exception_information[8], 0xc000003e);
+    assert_eq!(record.exception_information[9], 0x1234);
+
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxSigInfo as u32)
+        .expect("Couldn't find LinuxSigInfo");
+    let siginfo: serde_json::Value = serde_json::from_slice(stream).expect("siginfo is not JSON");
+    assert_eq!(siginfo["ssi_signo"], libc::SIGSYS);
+    assert_eq!(siginfo["ssi_errno"], -libc::EPERM);
+    assert_eq!(siginfo["ssi_syscall"], libc::SYS_getpid);
+    assert_eq!(siginfo["ssi_call_addr"], 0x1234);
+    assert_eq!(siginfo["sent_by_process"], false);
+}
+
+#[test]
+fn requested_exception() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("requested_exception")
+        .tempfile()
+        .unwrap();
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.set_requested_exception(RequestedException {
+        code: 0xdead,
+        flags: 2,
+        address: 0x1000,
+        parameters: (0..16).collect(),
+        reason: Some("main thread hung for 30 seconds".into()),
+    });
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let exception: MinidumpException = dump.get_stream().expect("Couldn't find exception");
+    let record = exception.raw.exception_record;
+    assert_eq!(record.exception_code, 0xdead);
+    assert_eq!(record.exception_flags, 2);
+    assert_eq!(record.exception_address, 0x1000);
+    assert_eq!(record.number_parameters, 15);
+    assert_eq!(
+        record.exception_information,
+        std::array::from_fn(|i| i as u64)
+    );
+
+    let comment = dump
+        .get_raw_stream(CommentStreamA as u32)
+        .expect("Couldn't find comment");
+    assert_eq!(comment, b"main thread hung for 30 seconds\0");
+
+    assert_soft_errors_in_minidump(&dump, &[json!({"TooManyExceptionParameters": 16})]);
+}
+
+#[test]
+fn annotations() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("annotations")
+        .tempfile()
+        .unwrap();
+
+    let tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let crashpad_info: MinidumpCrashpadInfo =
+        dump.get_stream().expect("Couldn't find crashpad info");
+    assert_eq!(
+        crashpad_info.simple_annotations,
+        [
+            ("channel".to_owned(), "nightly".to_owned()),
+            ("request_id".to_owned(), "3f2a".to_owned()),
+        ]
+        .into()
+    );
+    assert!(crashpad_info.module_list.is_empty());
+}
+
+#[cfg(target_env = "gnu")]
+#[test]
+fn abort_message() {
+    let mut child = start_child_and_return(&["spawn_assert_wait"]);
+    wait_for_threads(&mut child, 1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("abort_message")
+        .tempfile()
+        .unwrap();
+
+    MinidumpWriterConfig::new(pid, pid)
+        .write(&mut tmpfile)
+        .expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let message = dump
+        .get_raw_stream(MDExtStreamType::LinuxAbortMessage as u32)
+        .expect("Couldn't find abort message");
+    let message = std::str::from_utf8(message).expect("abort message is not UTF-8");
+    assert!(
+        message.contains("test.rs:42: spawn_assert_wait: Assertion `1 + 1 == 3' failed"),
+        "{message}"
+    );
+}
+
+#[test]
+fn jit_code() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("jit_code")
+        .tempfile()
+        .unwrap();
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.capture_jit_code();
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let modules: MinidumpModuleList = dump.get_stream().expect("Couldn't find module list");
+    let jit_module = modules
+        .iter()
+        .find(|m| m.name.starts_with("[jit:"))
+        .expect("Couldn't find JIT module");
+
+    // The code described by the program headers of tests/tiny.elf
+    assert_eq!(jit_module.base_address(), 0x40030a);
+    assert_eq!(jit_module.size(), 7);
+    assert_eq!(
+        jit_module.code_identifier().unwrap().as_str(),
+        "0102030405060708090a0b0c0d0e0f10"
+    );
+
+    // The object itself is in the memory list at the address in the name
+    let symfile_address = u64::from_str_radix(
+        jit_module
+            .name
+            .trim_start_matches("[jit:0x")
+            .trim_end_matches(']'),
+        16,
+    )
+    .unwrap();
+    let memory_list: MinidumpMemoryList = dump.get_stream().expect("Couldn't find memory list");
+    let region = memory_list
+        .memory_at_address(symfile_address)
+        .expect("JIT object is not in the memory list");
+    assert_eq!(region.base_address, symfile_address);
+    assert_eq!(region.bytes, include_bytes!("tiny.elf"));
+}
+
+#[test]
+fn jit_code_out_of_budget() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("jit_code_out_of_budget")
+        .tempfile()
+        .unwrap();
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.capture_jit_code()
+        .stream_budget(MemoryListStream as u32, std::time::Duration::ZERO);
+    tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    // The module is still listed, but the object isn't copied
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let modules: MinidumpModuleList = dump.get_stream().expect("Couldn't find module list");
+    let jit_module = modules
+        .iter()
+        .find(|m| m.name.starts_with("[jit:"))
+        .expect("Couldn't find JIT module");
+    let symfile_address = u64::from_str_radix(
+        jit_module
+            .name
+            .trim_start_matches("[jit:0x")
+            .trim_end_matches(']'),
+        16,
+    )
+    .unwrap();
+    let memory_list: MinidumpMemoryList = dump.get_stream().expect("Couldn't find memory list");
+    assert!(memory_list.memory_at_address(symfile_address).is_none());
+    assert_soft_errors_in_minidump(&dump, &[json!({"StreamSkipped": MemoryListStream as u32})]);
+}
+
+#[test]
+fn perf_map() {
+    let mut child = start_child_and_return(&["spawn_perf_map_wait"]);
+    let pid = child.id() as i32;
+
+    let mut f = BufReader::new(child.stdout.as_mut().expect("Can't open stdout"));
+    let mut buf = String::new();
+    f.read_line(&mut buf)
+        .expect("Couldn't read address provided by child");
+    let code = u64::from_str_radix(buf.trim(), 16).expect("unable to parse code address");
+
+    let write_dump = |options: PerfMapOptions| {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("perf_map")
+            .tempfile()
+            .unwrap();
+        let mut tmp = MinidumpWriterConfig::new(pid, pid);
+        tmp.capture_perf_map(options);
+        tmp.write(&mut tmpfile).expect("Could not write minidump");
+        Minidump::read_path(tmpfile.path()).expect("Failed to read minidump")
+    };
+
+    let all = write_dump(PerfMapOptions::default());
+    // No thread is executing the JIT code
+    let near_ips = write_dump(PerfMapOptions {
+        ip_window: Some(0x100),
+        ..Default::default()
+    });
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+    std::fs::remove_file(format!("/tmp/perf-{pid}.map")).unwrap();
+
+    let stream = all
+        .get_raw_stream(MDExtStreamType::LinuxPerfMap as u32)
+        .expect("Couldn't find perf map");
+    assert_eq!(
+        std::str::from_utf8(stream).unwrap(),
+        format!(
+            "{:x} 40 jitted_one\n{:x} 20 Lcom/example/Foo;::bar (int)\n",
+            code,
+            code + 0x40
+        )
+    );
+
+    assert!(
+        near_ips
+            .get_raw_stream(MDExtStreamType::LinuxPerfMap as u32)
+            .is_err()
+    );
+}
+
+#[test]
+fn synthetic_modules() {
+    // This child also maps an anonymous executable page
+    let mut child = start_child_and_return(&["spawn_perf_map_wait"]);
+    let pid = child.id() as i32;
+
+    let mut f = BufReader::new(child.stdout.as_mut().expect("Can't open stdout"));
+    let mut buf = String::new();
+    f.read_line(&mut buf)
+        .expect("Couldn't read address provided by child");
+    let code = u64::from_str_radix(buf.trim(), 16).expect("unable to parse code address");
+
+    let write_dump = |synthesize: bool| {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("synthetic_modules")
+            .tempfile()
+            .unwrap();
+        let mut tmp = MinidumpWriterConfig::new(pid, pid);
+        if synthesize {
+            tmp.synthesize_anonymous_modules();
+        }
+        tmp.write(&mut tmpfile).expect("Could not write minidump");
+        Minidump::read_path(tmpfile.path()).expect("Failed to read minidump")
+    };
+
+    let with = write_dump(true);
+    let without = write_dump(false);
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+    std::fs::remove_file(format!("/tmp/perf-{pid}.map")).unwrap();
+
+    let name = format!("[anon:exec:{code:#x}]");
+    let modules: MinidumpModuleList = with.get_stream().expect("Couldn't find module list");
+    let module = modules
+        .iter()
+        .find(|m| m.name == name)
+        .expect("Couldn't find synthetic module");
+    assert_eq!(module.base_address(), code);
+    let page: Vec<u8> = (0..4096).map(|idx| (idx % 251) as u8).collect();
+    assert_eq!(
+        module.code_identifier().unwrap().as_str(),
+        module_reader::build_id_from_bytes(&page)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    );
+
+    let modules: MinidumpModuleList = without.get_stream().expect("Couldn't find module list");
+    assert!(!modules.iter().any(|m| m.name.starts_with("[anon:exec:")));
+}
+
+#[test]
+fn debug_info() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("debug_info")
+        .tempfile()
+        .unwrap();
+
+    let mut tmp = MinidumpWriterConfig::new(pid, pid);
+    tmp.capture_gnu_debugdata();
+    let in_memory = tmp.write(&mut tmpfile).expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let modules: MinidumpModuleList = dump.get_stream().expect("Couldn't find module list");
+
+    // Whatever the system libraries carry should be recorded
+    let mut expected = Vec::new();
+    for module in modules.iter() {
+        let Ok(data) = std::fs::read(module.name.as_str()) else {
+            continue;
+        };
+        let reader = module_reader::ModuleReader::new(data.as_slice()).unwrap();
+        let debug_link = reader.debug_link_from_section().ok();
+        let debug_data = reader
+            .debug_data_from_section()
+            .ok()
+            .map(|d| d.into_owned());
+        if debug_link.is_some() || debug_data.is_some() {
+            expected.push((module.name.clone(), debug_link, debug_data));
+        }
+    }
+    if expected.is_empty() {
+        assert!(
+            dump.get_raw_stream(MDExtStreamType::LinuxDebugInfo as u32)
+                .is_err()
+        );
+        return;
+    }
+
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxDebugInfo as u32)
+        .expect("Couldn't find LinuxDebugInfo");
+    let entries: serde_json::Value = serde_json::from_slice(stream).expect("not JSON");
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), expected.len());
+
+    for (name, debug_link, debug_data) in expected {
+        let entry = entries
+            .iter()
+            .find(|e| e["module"] == name.as_str())
+            .unwrap_or_else(|| panic!("{name} is missing"));
+        assert_eq!(
+            entry["debug_link"],
+            serde_json::to_value(&debug_link).unwrap()
+        );
+        match debug_data {
+            Some(data) => {
+                let rva = entry["debug_data"]["rva"].as_u64().unwrap() as usize;
+                let size = entry["debug_data"]["data_size"].as_u64().unwrap() as usize;
+                assert_eq!(&in_memory[rva..rva + size], data.as_slice());
+            }
+            None => assert!(entry["debug_data"].is_null()),
+        }
+    }
+}
+
+#[test]
+fn symbol_hints() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let write_dump = |hints: bool| {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("symbol_hints")
+            .tempfile()
+            .unwrap();
+        let mut tmp = MinidumpWriterConfig::new(pid, pid);
+        if hints {
+            tmp.capture_symbol_hints();
+        }
+        tmp.write(&mut tmpfile).expect("Could not write minidump");
+        Minidump::read_path(tmpfile.path()).expect("Failed to read minidump")
+    };
+
+    let with = write_dump(true);
+    let without = write_dump(false);
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    assert!(
+        without
+            .get_raw_stream(MDExtStreamType::LinuxSymbolHints as u32)
+            .is_err()
+    );
+
+    let stream = with
+        .get_raw_stream(MDExtStreamType::LinuxSymbolHints as u32)
+        .expect("Couldn't find LinuxSymbolHints");
+    let hints: serde_json::Value = serde_json::from_slice(stream).expect("not JSON");
+    let hints = hints.as_array().unwrap();
+
+    // The child is blocked in a syscall, so it is somewhere in libc, called
+    // from libc
+    let in_libc = |hint: &&serde_json::Value| {
+        hint["module"].as_str().unwrap().contains("/libc")
+            && !hint["symbol"].as_str().unwrap().is_empty()
+    };
+    assert!(
+        hints
+            .iter()
+            .filter(|hint| hint["source"] == "instruction_pointer")
+            .any(|hint| in_libc(&hint))
+    );
+    assert!(
+        hints
+            .iter()
+            .filter(|hint| hint["source"] == "stack")
+            .any(|hint| in_libc(&hint))
+    );
+}
+
+#[test]
+fn module_files() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let deleted = dir.path().join("libdeleted.so");
+    let replaced = dir.path().join("libreplaced.so");
+    let unchanged = dir.path().join("libunchanged.so");
+    for path in [&deleted, &replaced, &unchanged] {
+        std::fs::copy("tests/tiny.elf", path).unwrap();
+    }
+    let replaced_inode = std::fs::metadata(&replaced).unwrap().ino();
+
+    let mut child = start_child_and_return(&[
+        "spawn_map_files_wait",
+        deleted.to_str().unwrap(),
+        replaced.to_str().unwrap(),
+        unchanged.to_str().unwrap(),
+    ]);
+    let pid = child.id() as i32;
+
+    let mut f = BufReader::new(child.stdout.as_mut().expect("Can't open stdout"));
+    let mut buf = String::new();
+    f.read_line(&mut buf)
+        .expect("Couldn't read addresses provided by child");
+
+    // As a package manager would upgrade a library
+    std::fs::remove_file(&deleted).unwrap();
+    let upgrade = dir.path().join("upgrade.tmp");
+    std::fs::copy("tests/tiny.elf", &upgrade).unwrap();
+    std::fs::rename(&upgrade, &replaced).unwrap();
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("module_files")
+        .tempfile()
+        .unwrap();
+    MinidumpWriterConfig::new(pid, pid)
+        .write(&mut tmpfile)
+        .expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxModuleFiles as u32)
+        .expect("Couldn't find LinuxModuleFiles");
+    let modules: serde_json::Value = serde_json::from_slice(stream).expect("not JSON");
+    let module = |path: &std::path::Path| {
+        modules
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|m| m["module"] == path.to_str().unwrap())
+            .unwrap_or_else(|| panic!("{} is missing", path.display()))
+            .clone()
+    };
+
+    let module_deleted = module(&deleted);
+    assert_eq!(module_deleted["deleted"], true);
+    assert!(module_deleted["on_disk"].is_null());
+
+    let module_replaced = module(&replaced);
+    assert_eq!(
+        module_replaced["on_disk"]["inode"],
+        std::fs::metadata(&replaced).unwrap().ino()
+    );
+
+    let module_unchanged = module(&unchanged);
+    assert_eq!(module_unchanged["deleted"], false);
+    assert_eq!(module_unchanged["on_disk"]["size"], 785);
+
+    // Whether the mapped files can be inspected depends on privileges
+    if module_replaced["mapped"].is_null() {
+        assert_eq!(module_unchanged["source"], "process_root");
+    } else {
+        assert_eq!(module_deleted["source"], "map_files");
+        assert_eq!(module_unchanged["source"], "map_files");
+        assert_eq!(module_replaced["replaced"], true);
+        // The mapped file was unlinked by the rename
+        assert_eq!(module_replaced["deleted"], true);
+        assert_eq!(module_replaced["mapped"]["inode"], replaced_inode);
+        assert_eq!(module_unchanged["replaced"], false);
+        assert!(!module_deleted["mapped"].is_null());
+    }
+}
+
+#[test]
+fn container() {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut child = start_child_and_return(&["spawn_pid_namespace_wait"]);
+    let mut f = BufReader::new(child.stdout.as_mut().expect("Can't open stdout"));
+    let mut buf = String::new();
+    f.read_line(&mut buf)
+        .expect("Couldn't read pid provided by child");
+    let pid: Pid = buf.trim().parse().unwrap();
+    if pid == 0 {
+        // Creating namespaces needs privileges
+        child.wait().expect("Failed to wait on child");
+        return;
+    }
+
+    // Blame the process by its id in its own PID namespace
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("container")
+        .tempfile()
+        .unwrap();
+    MinidumpWriterConfig::new(pid, 1)
+        .write(&mut tmpfile)
+        .expect("Could not write minidump");
+
+    unsafe { libc::kill(pid, libc::SIGKILL) };
+    child.wait().expect("Failed to wait on child");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let exception: MinidumpException = dump.get_stream().expect("Couldn't find exception");
+    assert_eq!(exception.raw.thread_id, pid as u32);
+
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxContainer as u32)
+        .expect("Couldn't find LinuxContainer");
+    let container: serde_json::Value = serde_json::from_slice(stream).expect("not JSON");
+    assert_eq!(container["pid"], pid);
+    assert_eq!(container["namespace_pids"], json!([pid, 1]));
+    assert_eq!(
+        container["threads"],
+        json!([{"tid": pid, "namespace_tids": [pid, 1]}])
+    );
+    assert_eq!(container["hostname"], "minidump-container");
+    assert!(!container["cgroups"].as_array().unwrap().is_empty());
+
+    let own_ns = |name: &str| {
+        std::fs::metadata(format!("/proc/self/ns/{name}"))
+            .unwrap()
+            .ino()
+    };
+    assert_ne!(container["namespaces"]["pid"], own_ns("pid"));
+    assert_ne!(container["namespaces"]["uts"], own_ns("uts"));
+    assert_eq!(container["namespaces"]["net"], own_ns("net"));
+}
+
+#[test]
+fn process_gone() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("process_gone")
+        .tempfile()
+        .unwrap();
+    let result = MinidumpWriterConfig::new(pid, pid).write(&mut tmpfile);
+    assert!(
+        matches!(
+            result,
+            Err(WriterError::InitError(
+                minidump_writer::minidump_writer::errors::InitError::ProcessGone
+            ))
+        ),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn watchdog_with_threads_already_traced() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("watchdog_with_threads_already_traced")
+        .tempfile()
+        .unwrap();
+    let mut config = MinidumpWriterConfig::new(pid, pid);
+    config
+        .watchdog(std::time::Duration::from_secs(5))
+        .threads_already_traced();
+    let result = config.write(&mut tmpfile);
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    assert!(
+        matches!(
+            result,
+            Err(WriterError::InitError(
+                minidump_writer::minidump_writer::errors::InitError::WatchdogWithThreadsAlreadyTraced
+            ))
+        ),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn preopened_dirs() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let dirs = minidump_writer::PreopenedDirs::open(pid).expect("failed to open directories");
+    let reader_dirs =
+        minidump_writer::PreopenedDirs::open(pid).expect("failed to open directories");
+    let empty = tempfile::tempdir().unwrap();
+    let empty_path = std::ffi::CString::new(empty.path().to_str().unwrap()).unwrap();
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("preopened_dirs")
+        .tempfile()
+        .unwrap();
+
+    // Confine the writer by making absolute paths resolve in an empty directory, for its thread
+    // only
+    let written = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if unsafe { libc::unshare(libc::CLONE_FS) } != 0
+                    || unsafe { libc::chroot(empty_path.as_ptr()) } != 0
+                {
+                    return false;
+                }
+                let mut config = MinidumpWriterConfig::new(pid, pid);
+                config.preopened_dirs(dirs);
+                config
+                    .write(&mut tmpfile)
+                    .expect("Could not write minidump");
+
+                // The process's memory can be read from its procfs directory as well
+                let mut config = MinidumpWriterConfig::new(pid, pid);
+                config.preopened_dirs(reader_dirs);
+                let writer = config
+                    .build_for_testing(error_graph::ErrorList::default())
+                    .expect("Could not init writer");
+                let reader = minidump_writer::process_reader::ProcessReader::for_file(
+                    &writer.process_inspector,
+                )
+                .expect("Could not open the process's memory");
+                reader
+                    .read(writer.mappings[0].start_address, &mut [0u8; 8])
+                    .expect("Could not read the process's memory");
+                true
+            })
+            .join()
+            .unwrap()
+    });
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    if !written {
+        // Confining the writer needs privileges
+        return;
+    }
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let module_list: MinidumpModuleList = dump
+        .get_stream()
+        .expect("Couldn't find stream MinidumpModuleList");
+    let main_module = module_list
+        .main_module()
+        .expect("Could not get main module");
+    assert!(main_module.debug_identifier().is_some());
+
+    for stream in [LinuxCpuInfo, LinuxProcStatus, LinuxMaps, LinuxLsbRelease] {
+        assert!(
+            !dump.get_raw_stream(stream.into()).unwrap().is_empty(),
+            "{stream:?} is empty"
+        );
+    }
+    let thread_names: MinidumpThreadNames = dump
+        .get_stream()
+        .expect("Couldn't find MinidumpThreadNames");
+    assert_eq!(thread_names.get_name(pid as u32).as_deref(), Some("test"));
+}
+
+#[test]
+fn proc_files_through_process_dir() {
+    let mut child = start_child_and_wait_for_threads(2);
+    let pid = child.id() as i32;
+    let blamed_thread: i32 = std::fs::read_dir(format!("/proc/{pid}/task"))
+        .unwrap()
+        .map(|entry| {
+            entry
+                .unwrap()
+                .file_name()
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .find(|&tid| tid != pid)
+        .unwrap();
+
+    // Only the process's own procfs directory can be used to read its files, also those of the
+    // blamed thread
+    let empty = tempfile::tempdir().unwrap();
+    let empty_path = std::ffi::CString::new(empty.path().to_str().unwrap()).unwrap();
+    let mut dirs = minidump_writer::PreopenedDirs::open(pid).expect("failed to open directories");
+    dirs.proc = std::fs::File::open(empty.path())
+        .map(std::os::fd::OwnedFd::from)
+        .unwrap()
+        .into();
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("proc_files_through_process_dir")
+        .tempfile()
+        .unwrap();
+
+    let written = std::thread::scope(|scope| {
+        scope
+            .spawn(|| {
+                if unsafe { libc::unshare(libc::CLONE_FS) } != 0
+                    || unsafe { libc::chroot(empty_path.as_ptr()) } != 0
+                {
+                    return false;
+                }
+                let mut config = MinidumpWriterConfig::new(pid, blamed_thread);
+                config.preopened_dirs(dirs);
+                config
+                    .write(&mut tmpfile)
+                    .expect("Could not write minidump");
+                true
+            })
+            .join()
+            .unwrap()
+    });
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    if !written {
+        // Confining the writer needs privileges
+        return;
+    }
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    for stream in [
+        LinuxProcStatus,
+        LinuxCmdLine,
+        LinuxEnviron,
+        LinuxAuxv,
+        MozLinuxLimits,
+    ] {
+        assert!(
+            !dump.get_raw_stream(stream.into()).unwrap().is_empty(),
+            "{stream:?} is empty"
+        );
+    }
+}
+
+#[test]
+fn no_ptrace() {
+    let num_of_threads = 3;
+    let mut child = start_child_and_wait_for_threads(num_of_threads);
+    let pid = child.id() as i32;
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("no_ptrace")
+        .tempfile()
+        .unwrap();
+    let mut config = MinidumpWriterConfig::new(pid, pid);
+    config.no_ptrace();
+    config
+        .write(&mut tmpfile)
+        .expect("Could not write minidump");
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let system_info: MinidumpSystemInfo = dump.get_stream().expect("Couldn't find SystemInfo");
+    let thread_list: MinidumpThreadList =
+        dump.get_stream().expect("Couldn't find MinidumpThreadList");
+    assert_eq!(thread_list.threads.len(), num_of_threads);
+
+    let stream = dump
+        .get_raw_stream(MDExtStreamType::LinuxDegradedDump as u32)
+        .expect("Couldn't find LinuxDegradedDump");
+    let degraded: serde_json::Value = serde_json::from_slice(stream).expect("not JSON");
+    assert_eq!(degraded["mode"], "no_ptrace");
+    let degraded_threads = degraded["threads"].as_array().unwrap();
+    assert_eq!(degraded_threads.len(), num_of_threads);
+
+    for thread in &thread_list.threads {
+        let context = thread
+            .context(&system_info, None)
+            .expect("thread has no context");
+        let degraded_thread = degraded_threads
+            .iter()
+            .find(|t| t["tid"] == thread.raw.thread_id)
+            .expect("thread is missing from the degraded stream");
+        assert_eq!(
+            degraded_thread["stack_pointer"],
+            context.get_stack_pointer()
+        );
+        assert_eq!(
+            degraded_thread["instruction_pointer"],
+            context.get_instruction_pointer()
+        );
+        assert_ne!(context.get_instruction_pointer(), 0);
+        // The stack around the stack pointer is captured
+        assert!(thread.raw.stack.memory.data_size > 0);
+        assert!(thread.raw.stack.start_of_memory_range <= context.get_stack_pointer());
+    }
+}
+
+#[test]
+fn threads_already_traced() {
+    let num_of_threads = 3;
+    let mut child = start_child_and_wait_for_threads(num_of_threads);
+    let pid = child.id() as i32;
+
+    // Trace the threads as a supervisor would
+    let tids: Vec<Pid> = std::fs::read_dir(format!("/proc/{pid}/task"))
+        .unwrap()
+        .map(|entry| {
+            entry
+                .unwrap()
+                .file_name()
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .collect();
+    for &tid in &tids {
+        let rv = unsafe { libc::ptrace(libc::PTRACE_ATTACH, tid, 0, 0) };
+        assert_eq!(rv, 0, "failed to attach to {tid}");
+        let mut status = 0;
+        assert_eq!(
+            unsafe { libc::waitpid(tid, &mut status, libc::__WALL) },
+            tid
+        );
+        assert!(libc::WIFSTOPPED(status));
+    }
+
+    let mut tmpfile = tempfile::Builder::new()
+        .prefix("threads_already_traced")
+        .tempfile()
+        .unwrap();
+    let mut config = MinidumpWriterConfig::new(pid, pid);
+    config.threads_already_traced();
+    config
+        .write(&mut tmpfile)
+        .expect("Could not write minidump");
+
+    // The writer left the threads traced by this thread
+    let this_thread = unsafe { libc::syscall(libc::SYS_gettid) } as Pid;
+    for &tid in &tids {
+        let status = std::fs::read_to_string(format!("/proc/{pid}/task/{tid}/status")).unwrap();
+        let tracer = status
+            .lines()
+            .find_map(|line| line.strip_prefix("TracerPid:"))
+            .unwrap()
+            .trim();
+        assert_eq!(tracer, this_thread.to_string());
+    }
+
+    for &tid in &tids {
+        unsafe { libc::ptrace(libc::PTRACE_DETACH, tid, 0, 0) };
+    }
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    let dump = Minidump::read_path(tmpfile.path()).expect("Failed to read minidump");
+    let system_info: MinidumpSystemInfo = dump.get_stream().expect("Couldn't find SystemInfo");
+    let thread_list: MinidumpThreadList =
+        dump.get_stream().expect("Couldn't find MinidumpThreadList");
+    assert_eq!(thread_list.threads.len(), num_of_threads);
+    for thread in &thread_list.threads {
+        let context = thread
+            .context(&system_info, None)
+            .expect("thread has no context");
+        assert_ne!(context.get_instruction_pointer(), 0);
+    }
+}
+
+#[test]
+fn watchdog() {
+    // Whether all threads of the process are stopped by a signal or a tracer
+    let is_stopped = |pid: u32| {
+        std::fs::read_dir(format!("/proc/{pid}/task"))
+            .unwrap()
+            .all(|entry| {
+                let stat = std::fs::read_to_string(entry.unwrap().path().join("stat")).unwrap();
+                let state = stat.rsplit_once(')').unwrap().1.trim_start();
+                state.starts_with(['T', 't'])
+            })
+    };
+
+    for (behavior, signal) in [("abort", libc::SIGABRT), ("hang", libc::SIGKILL)] {
+        let mut child = start_child_and_wait_for_threads(2);
+        let pid = child.id();
+
+        let mut dumper =
+            start_child_and_return(&["dump_with_watchdog", &pid.to_string(), behavior]);
+        let status = dumper.wait().expect("Failed to wait on the dumper");
+        assert_eq!(
+            std::os::unix::process::ExitStatusExt::signal(&status),
+            Some(signal),
+            "{behavior}: {status}"
+        );
+
+        // The sentinel continues the process once the dumper is gone
+        let end = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while is_stopped(pid) {
+            assert!(
+                std::time::Instant::now() < end,
+                "{behavior}: the process is still stopped"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        child.kill().expect("Failed to kill process");
+        child.wait().expect("Failed to wait on killed process");
+    }
+}
+
+#[test]
+fn deadline() {
+    let num_of_threads = 3;
+    let mut child = start_child_and_wait_for_threads(num_of_threads);
+    let pid = child.id() as i32;
+
+    let write = |configure: &dyn Fn(&mut MinidumpWriterConfig)| {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("deadline")
+            .tempfile()
+            .unwrap();
+        let mut config = MinidumpWriterConfig::new(pid, pid);
+        configure(&mut config);
+        config
+            .write(&mut tmpfile)
+            .expect("Could not write minidump");
+        tmpfile
+    };
+    let past_deadline = write(&|config| {
+        config.deadline(std::time::Duration::ZERO);
+    });
+    let out_of_budget = write(&|config| {
+        config.stream_budget(HandleDataStream as u32, std::time::Duration::ZERO);
+    });
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    // Once the deadline has passed, only the streams needed to process the
+    // minidump are written, and only the crashing thread's stack
+    let dump = Minidump::read_path(past_deadline.path()).expect("Failed to read minidump");
+    let thread_list: MinidumpThreadList =
+        dump.get_stream().expect("Couldn't find MinidumpThreadList");
+    assert_eq!(thread_list.threads.len(), num_of_threads);
+    for thread in &thread_list.threads {
+        assert_eq!(
+            thread.raw.stack.memory.data_size != 0,
+            thread.raw.thread_id == pid as u32
+        );
+    }
+    let module_list: MinidumpModuleList = dump.get_stream().expect("Couldn't find ModuleList");
+    assert!(module_list.iter().next().is_some());
+    let _: MinidumpSystemInfo = dump.get_stream().expect("Couldn't find SystemInfo");
+    assert!(dump.get_stream::<MinidumpThreadNames>().is_err());
+    assert_soft_errors_in_minidump(
+        &dump,
+        &[
+            json!({"StreamBudgetExceeded": ThreadListStream as u32}),
+            json!({"StreamSkipped": ThreadNamesStream as u32}),
+        ],
+    );
+
+    // A stream out of budget is skipped without affecting the others
+    let dump = Minidump::read_path(out_of_budget.path()).expect("Failed to read minidump");
+    let thread_list: MinidumpThreadList =
+        dump.get_stream().expect("Couldn't find MinidumpThreadList");
+    for thread in &thread_list.threads {
+        assert_ne!(thread.raw.stack.memory.data_size, 0);
+    }
+    let _: MinidumpThreadNames = dump.get_stream().expect("Couldn't find ThreadNames");
+    assert!(dump.get_raw_stream(HandleDataStream.into()).is_err());
+    assert_soft_errors_in_minidump(&dump, &[json!({"StreamSkipped": HandleDataStream as u32})]);
+}
@@ -2,8 +2,10 @@
 
 use {
     common::*,
-    minidump::Minidump,
-    minidump_writer::{FailSpotName, minidump_writer::MinidumpWriterConfig},
+    minidump::{Minidump, MinidumpThreadList},
+    minidump_writer::{
+        FailSpotName, minidump_format::MDExtStreamType, minidump_writer::MinidumpWriterConfig,
+    },
     serde_json::json,
 };
 
@@ -101,3 +103,103 @@ fn soft_error_stream_content() {
     let dump = Minidump::read_path(tmpfile.path()).expect("failed to read minidump");
     assert_soft_errors_in_minidump(&dump, &expected_errors);
 }
+
+#[test]
+fn exited_threads() {
+    let mut child = start_child_and_return(&["spawn_thread_churn_wait"]);
+    wait_for_threads(&mut child, 1);
+    let pid = child.id() as i32;
+
+    // Threads keep being created and exiting while the process isn't stopped,
+    // so some are likely to exit between being listed and being suspended
+    let mut fail_client = FailSpotName::testing_client();
+    fail_client.set_enabled(FailSpotName::StopProcess, true);
+
+    let mut seen_exited = false;
+    for _ in 0..20 {
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("exited_threads")
+            .tempfile()
+            .unwrap();
+        MinidumpWriterConfig::new(pid, pid)
+            .write(&mut tmpfile)
+            .expect("could not write minidump");
+
+        let dump = Minidump::read_path(tmpfile.path()).expect("failed to read minidump");
+        let thread_list: MinidumpThreadList = dump.get_stream().expect("missing thread list");
+        let exited: serde_json::Value = serde_json::from_slice(
+            dump.get_raw_stream(MDExtStreamType::LinuxExitedThreads as u32)
+                .expect("missing exited threads stream"),
+        )
+        .expect("expected json");
+
+        assert_eq!(exited["stable"], json!(true));
+        assert!(exited["rescans"].as_u64().unwrap() >= 1);
+        for thread in exited["threads"].as_array().unwrap() {
+            let tid = thread["tid"].as_u64().unwrap() as u32;
+            assert!(thread_list.get_thread(tid).is_none());
+            assert!(
+                ["before_suspension", "after_suspension"]
+                    .contains(&thread["exited"].as_str().unwrap())
+            );
+            seen_exited = true;
+        }
+        if seen_exited {
+            break;
+        }
+    }
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+    assert!(seen_exited, "no thread exited during 20 dumps");
+}
+
+#[test]
+fn interrupt_failure_leaves_threads_untraced() {
+    // The seized threads are either in a group-stop already, or still running if the process
+    // couldn't be stopped
+    for stop_fails in [false, true] {
+        let mut child = start_child_and_wait_for_threads(3);
+        let pid = child.id() as i32;
+
+        let mut tmpfile = tempfile::Builder::new()
+            .prefix("interrupt_failure_leaves_threads_untraced")
+            .tempfile()
+            .unwrap();
+
+        // Every thread is seized, but can't be interrupted
+        let mut fail_client = FailSpotName::testing_client();
+        fail_client.set_enabled(FailSpotName::InterruptThread, true);
+        fail_client.set_enabled(FailSpotName::StopProcess, stop_fails);
+
+        MinidumpWriterConfig::new(pid, pid)
+            .write(&mut tmpfile)
+            .expect("could not write minidump");
+        drop(fail_client);
+
+        let tracers: Vec<String> = std::fs::read_dir(format!("/proc/{pid}/task"))
+            .unwrap()
+            .map(|entry| {
+                let status = std::fs::read_to_string(entry.unwrap().path().join("status")).unwrap();
+                let tracer = status
+                    .lines()
+                    .find_map(|line| line.strip_prefix("TracerPid:"))
+                    .unwrap();
+                tracer.trim().to_owned()
+            })
+            .collect();
+        child.kill().expect("Failed to kill process");
+        child.wait().expect("Failed to wait on killed process");
+
+        // No thread may be left traced by the dumper
+        assert_eq!(tracers, ["0", "0", "0"], "stop_fails: {stop_fails}");
+
+        let dump = Minidump::read_path(tmpfile.path()).expect("failed to read minidump");
+        let soft_errors = read_minidump_soft_errors_or_panic(&dump).to_string();
+        assert!(
+            soft_errors
+                .contains(r#"{"SuspendThreadFailed":{"Local":{"PtraceInterruptFailed":1}}}"#),
+            "stop_fails: {stop_fails}: {soft_errors}"
+        );
+    }
+}
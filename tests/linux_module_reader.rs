@@ -61,3 +61,55 @@ fn soname_section() {
     let soname = reader.soname_from_sections().unwrap();
     assert_eq!(soname, "libfoo.so.1");
 }
+
+#[test]
+fn dynamic_symbols() {
+    // The test binary exports its annotation registry
+    let test_binary = std::fs::read(env!("CARGO_BIN_EXE_test")).unwrap();
+    let reader = ModuleReader::new(SliceModuleMemoryReader(&test_binary)).unwrap();
+
+    let from_program_headers = reader.dynamic_symbols_from_program_headers().unwrap();
+    let from_sections = reader.dynamic_symbols_from_sections().unwrap();
+    assert_eq!(from_program_headers, from_sections);
+
+    let symbol = from_sections
+        .iter()
+        .find(|sym| sym.name == "minidump_writer_annotations")
+        .expect("registry symbol not found");
+    assert_eq!(symbol.symbol_type, goblin::elf::sym::STT_OBJECT);
+    assert_ne!(symbol.address, 0);
+}
+
+/// `tiny.elf` with a `.gnu_debuglink` to `tiny.debug` (containing
+/// "tiny debug info") and a `.gnu_debugdata` of "minidebuginfo" compressed
+/// with xz, added with objcopy
+const TINY_DEBUGLINK_ELF: &[u8] = include_bytes!("tiny-debuglink.elf");
+
+#[test]
+fn debug_link() {
+    let reader = ModuleReader::new(SliceModuleMemoryReader(TINY_DEBUGLINK_ELF)).unwrap();
+    let link = reader.debug_link_from_section().unwrap();
+    assert_eq!(link.file_name, "tiny.debug");
+    assert_eq!(link.crc, 0x6e89e81b);
+
+    let reader = ModuleReader::new(SliceModuleMemoryReader(TINY_ELF)).unwrap();
+    assert!(matches!(
+        reader.debug_link_from_section(),
+        Err(ModuleReaderError::NoDebugLinkSection)
+    ));
+}
+
+#[test]
+fn debug_data() {
+    let reader = ModuleReader::new(SliceModuleMemoryReader(TINY_DEBUGLINK_ELF)).unwrap();
+    let data = reader.debug_data_from_section().unwrap();
+    // The xz stream header magic
+    assert!(data.starts_with(b"\xfd7zXZ\0"));
+    assert_eq!(data.len(), 80);
+
+    let reader = ModuleReader::new(SliceModuleMemoryReader(TINY_ELF)).unwrap();
+    assert!(matches!(
+        reader.debug_data_from_section(),
+        Err(ModuleReaderError::NoDebugDataSection)
+    ));
+}
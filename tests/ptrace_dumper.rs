@@ -4,7 +4,7 @@
 use {
     common::*,
     error_graph::ErrorList,
-    minidump_writer::minidump_writer::MinidumpWriterConfig,
+    minidump_writer::{minidump_writer::MinidumpWriterConfig, probe::ReaderStrategy},
     std::{
         convert::TryInto,
         ffi::c_void,
@@ -394,3 +394,113 @@ fn sanitizes_stack_copies() {
     assert_eq!(waitres.code(), None);
     assert_eq!(status, libc::SIGKILL);
 }
+
+#[test]
+fn probe() {
+    let mut child = start_child_and_wait_for_threads(1);
+    let pid = child.id() as i32;
+
+    let report = minidump_writer::probe::probe(pid);
+
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait on killed process");
+
+    assert!(report.dumper_is_ancestor);
+    assert_eq!(report.dumpable, Some(true));
+    assert_eq!(report.dumper_credentials, report.process_credentials);
+    assert!(report.ptrace_attach.passed(), "{report:?}");
+    assert!(report.ptrace_peekdata.passed(), "{report:?}");
+    assert!(report.problems.is_empty(), "{report:?}");
+
+    let expected_strategy = if report.process_vm_readv.passed() {
+        ReaderStrategy::VirtualMem
+    } else if report.proc_mem.passed() {
+        ReaderStrategy::File
+    } else {
+        ReaderStrategy::Ptrace
+    };
+    assert_eq!(report.selected_strategy, Some(expected_strategy));
+
+    // The report is meant to be logged or sent along with failed dumps
+    serde_json::to_string(&report).expect("failed to serialize report");
+}
+
+#[test]
+fn suspends_many_threads() {
+    disabled_on_ci_and_android!();
+
+    let num_of_threads = 2000;
+    let mut child = start_child_and_wait_for_threads(num_of_threads);
+    let pid = child.id() as i32;
+    let tids: Vec<i32> = std::fs::read_dir(format!("/proc/{pid}/task"))
+        .unwrap()
+        .map(|entry| {
+            entry
+                .unwrap()
+                .file_name()
+                .to_str()
+                .unwrap()
+                .parse()
+                .unwrap()
+        })
+        .collect();
+
+    // Only used for its process inspector, which doesn't suspend the threads
+    // with ptrace disabled
+    let mut config = MinidumpWriterConfig::new(pid, pid);
+    config.no_ptrace();
+    let unsuspended = config
+        .build_for_testing(ErrorList::default())
+        .expect("Couldn't init dumper");
+    let inspector = &unsuspended.process_inspector;
+    let resume_all = || {
+        for &tid in &tids {
+            inspector
+                .resume_thread(tid)
+                .expect("Couldn't resume thread");
+        }
+    };
+
+    // Attaching to one thread after the other, as the writer used to
+    let start = std::time::Instant::now();
+    for &tid in &tids {
+        inspector
+            .suspend_thread(tid)
+            .expect("Couldn't suspend thread");
+    }
+    let one_by_one = start.elapsed();
+    resume_all();
+
+    // Asking every thread to stop before waiting for any, as the writer does
+    let start = std::time::Instant::now();
+    for &tid in &tids {
+        inspector
+            .interrupt_thread(tid)
+            .expect("Couldn't interrupt thread");
+    }
+    for &tid in &tids {
+        inspector
+            .wait_for_interrupt(tid)
+            .expect("Couldn't wait for thread");
+    }
+    let concurrently = start.elapsed();
+    resume_all();
+
+    assert!(
+        concurrently < one_by_one,
+        "suspending {} threads one by one took {one_by_one:?}, concurrently {concurrently:?}",
+        tids.len()
+    );
+    drop(unsuspended);
+
+    let dumper = assert_no_soft_errors!(
+        soft_errors,
+        MinidumpWriterConfig::new(pid, pid).build_for_testing(&mut soft_errors)
+    )
+    .expect("Couldn't init dumper");
+    assert_eq!(dumper.threads.len(), tids.len());
+
+    drop(dumper);
+    child.kill().expect("Failed to kill process");
+    child.wait().expect("Failed to wait for child");
+}